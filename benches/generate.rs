@@ -0,0 +1,43 @@
+//! Benchmarks password generation, by running `makepass --benchmark` with a
+//! few sets of options and printing what it reports. This doesn't use a
+//! benchmarking framework, since makepass is only a binary; --benchmark does
+//! the measuring from inside it.
+//!
+//! Run with `cargo bench --features count-allocations` to also compare the
+//! allocations made by checking complete passwords against a tight maximum
+//! length with those made by abandoning them early, and those made by
+//! rendering passwords with `to_string` with those made by rendering them
+//! into a reused buffer.
+
+use std::process::Command;
+
+const CASES: &[(&str, &[&str])] = &[
+    ("default options", &[]),
+    (
+        "--max-length 24",
+        &["--max-length", "24", "--min-length", "0"],
+    ),
+    (
+        "--max-length 20",
+        &["--max-length", "20", "--min-length", "0"],
+    ),
+];
+
+fn main() {
+    for (name, args) in CASES {
+        let output = Command::new(env!("CARGO_BIN_EXE_makepass"))
+            .arg("--benchmark")
+            .args(["--log-level", "off"])
+            .args(*args)
+            .env_remove("MAKEPASS_WORDLIST")
+            .output()
+            .expect("failed to run makepass");
+
+        assert!(output.status.success(), "makepass failed: {:?}", output);
+
+        println!("{}:", name);
+        for line in String::from_utf8_lossy(&output.stderr).lines() {
+            println!("    {}", line);
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Benchmarks printing many passwords with --num-passwords, which renders
+//! each one into a reused buffer. --benchmark (see benches/generate.rs)
+//! doesn't print anything, so it doesn't cover this.
+//!
+//! With `cargo bench --features count-allocations`, benches/generate.rs also
+//! reports the allocations made rendering passwords with a reused buffer,
+//! compared to `to_string`.
+
+use std::process::{Command, Stdio};
+use std::time::Instant;
+
+const NUM_PASSWORDS: usize = 200_000;
+
+const CASES: &[(&str, &[&str])] = &[
+    ("default options", &[]),
+    ("--format csv", &["--format", "csv"]),
+    (
+        "--output-template",
+        &["--output-template", "{words} {numeral}"],
+    ),
+    ("--show-words-only", &["--show-words-only"]),
+];
+
+fn main() {
+    for (name, args) in CASES {
+        let start = Instant::now();
+
+        let status = Command::new(env!("CARGO_BIN_EXE_makepass"))
+            .args(["--num-passwords", &NUM_PASSWORDS.to_string()])
+            .args(["--seed", "1", "--log-level", "off"])
+            .args(*args)
+            .env_remove("MAKEPASS_WORDLIST")
+            .stdout(Stdio::null())
+            .status()
+            .expect("failed to run makepass");
+
+        let elapsed = start.elapsed().as_secs_f64();
+
+        assert!(status.success(), "makepass failed: {}", status);
+
+        println!(
+            "{}: printed {} passwords in {:.2} seconds ({:.0} passwords per second)",
+            name,
+            NUM_PASSWORDS,
+            elapsed,
+            NUM_PASSWORDS as f64 / elapsed
+        );
+    }
+}
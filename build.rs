@@ -24,9 +24,30 @@ fn main() {
         )
     });
 
+    // In addition to alphabetic characters, words may contain these punctuation
+    // characters, to allow for words like "well-being" or "o'clock"
+    let punctuation = env::var("WORDLIST_PUNCTUATION").unwrap_or_else(|_| "-'".to_string());
+
+    if let Some(c) = punctuation
+        .chars()
+        .find(|&c| c == '"' || c == '\\' || c.is_alphanumeric() || c.is_whitespace())
+    {
+        panic!("Invalid wordlist punctuation character {:?}", c)
+    }
+
+    // With the packed-wordlists feature, each wordlist is one string of
+    // newline-separated words, rather than an array of string literals; see
+    // `PackedWordlist` in src/wordlists.rs.
+    let packed = env::var_os("CARGO_FEATURE_PACKED_WORDLISTS").is_some();
+
     let mut file_buffer = String::new();
     let mut wordlist_names = Vec::new();
 
+    // The metadata for each wordlist, parsed from its header comments. Each
+    // symlinked wordlist instead records the name of the wordlist it links to,
+    // which is also used to generate `wordlist_alias`.
+    let mut wordlist_metas: Vec<(String, Result<WordlistMeta, String>)> = Vec::new();
+
     let output_file_path = Path::new(&env::var_os("OUT_DIR").unwrap()).join("wordlists_gen.rs");
     let mut output_file =
         BufWriter::new(fs::File::create(&output_file_path).unwrap_or_else(|err| {
@@ -80,14 +101,24 @@ fn main() {
                 }
 
                 let link_dest_name = link_dest.file_stem().unwrap().to_str().unwrap();
-                write!(
-                    &mut output_file,
-                    "#[allow(non_upper_case_globals)]\n\
-                     pub const {}: &[&str] = {};\n",
-                    wordlist_name, link_dest_name
-                )
+
+                if packed {
+                    writeln!(
+                        &mut output_file,
+                        "pub use self::{} as {};",
+                        link_dest_name, wordlist_name
+                    )
+                } else {
+                    write!(
+                        &mut output_file,
+                        "#[allow(non_upper_case_globals)]\n\
+                         pub const {}: &[&str] = {};\n",
+                        wordlist_name, link_dest_name
+                    )
+                }
                 .unwrap();
                 wordlist_names.push(wordlist_name.to_string());
+                wordlist_metas.push((wordlist_name.to_string(), Err(link_dest_name.to_string())));
                 continue;
             }
         }
@@ -103,7 +134,12 @@ fn main() {
         file_buffer.clear();
         wordlist.read_to_string(&mut file_buffer).unwrap();
 
-        let array_content = file_buffer
+        wordlist_metas.push((
+            wordlist_name.to_string(),
+            Ok(WordlistMeta::from_header(&file_buffer)),
+        ));
+
+        let words = file_buffer
             .as_str()
             .lines()
             .enumerate()
@@ -112,22 +148,39 @@ fn main() {
             .filter(|(_, line)| !line.starts_with('#'))
             .inspect(|(line_number, word)| {
                 assert!(
-                    word.chars().all(|c| c.is_alphabetic()),
+                    word.chars().any(|c| c.is_alphabetic())
+                        && word
+                            .chars()
+                            .all(|c| c.is_alphabetic() || punctuation.contains(c)),
                     "non-alphabetic word '{}' found in wordlist '{}' (line {})",
                     word,
                     path.display(),
                     line_number + 1,
                 )
             })
-            .map(|(_, word)| lazy_format!("\"{}\"", word))
-            .join_with(separators::Comma);
-
-        write!(
-            &mut output_file,
-            "#[allow(non_upper_case_globals)]\
-             pub const {}: &[&str] = &[{}];",
-            wordlist_name, array_content
-        )
+            .map(|(_, word)| word);
+
+        // Words can't contain quotes or backslashes, so they don't need to be
+        // escaped
+        if packed {
+            write!(
+                &mut output_file,
+                "#[allow(non_upper_case_globals)]\
+                 pub static {}: super::PackedWordlist = super::PackedWordlist::new(\"{}\");",
+                wordlist_name,
+                words.join_with("\\n"),
+            )
+        } else {
+            write!(
+                &mut output_file,
+                "#[allow(non_upper_case_globals)]\
+                 pub const {}: &[&str] = &[{}];",
+                wordlist_name,
+                words
+                    .map(|word| lazy_format!("\"{}\"", word))
+                    .join_with(separators::Comma),
+            )
+        }
         .unwrap();
 
         wordlist_names.push(wordlist_name.to_string());
@@ -154,10 +207,118 @@ fn main() {
         .try_for_each(|name| {
             write!(
                 &mut output_file,
-                "\"{name}\" => Some(wordlist_content::{name}),",
-                name = name
+                "\"{name}\" => Some(wordlist_content::{name}{words}),",
+                name = name,
+                words = if packed { ".words()" } else { "" },
+            )
+        })
+        .unwrap();
+    write!(&mut output_file, "\t\t_ => None,\n\t}}\n}}\n\n").unwrap();
+
+    write!(
+        &mut output_file,
+        "pub fn wordlist_meta(name: &str) -> Option<WordlistMeta> {{\n\tmatch name {{\n"
+    )
+    .unwrap();
+    wordlist_metas
+        .iter()
+        .try_for_each(|(name, meta)| match meta {
+            Ok(meta) => write!(
+                &mut output_file,
+                "{name:?} => Some(WordlistMeta {{ source: {source:?}, license: {license:?}, \
+                 locale: {locale:?} }}),",
+                name = name,
+                source = meta.source,
+                license = meta.license,
+                locale = meta.locale,
+            ),
+            Err(link_dest_name) => write!(
+                &mut output_file,
+                "{name:?} => wordlist_meta({dest:?}),",
+                name = name,
+                dest = link_dest_name,
+            ),
+        })
+        .unwrap();
+    write!(&mut output_file, "\t\t_ => None,\n\t}}\n}}\n\n").unwrap();
+
+    write!(
+        &mut output_file,
+        "pub fn wordlist_alias(name: &str) -> Option<&'static str> {{\n\tmatch name {{\n"
+    )
+    .unwrap();
+    wordlist_metas
+        .iter()
+        .filter_map(|(name, meta)| meta.as_ref().err().map(|dest| (name, dest)))
+        .try_for_each(|(name, link_dest_name)| {
+            write!(
+                &mut output_file,
+                "{name:?} => Some({dest:?}),",
+                name = name,
+                dest = link_dest_name,
+            )
+        })
+        .unwrap();
+    write!(&mut output_file, "\t\t_ => None,\n\t}}\n}}\n\n").unwrap();
+
+    // Symlinks are skipped, so that each locale maps to the wordlist itself
+    // rather than to one of its aliases
+    write!(
+        &mut output_file,
+        "pub fn wordlist_for_locale(locale: &str) -> Option<&'static str> {{\n\tmatch locale {{\n"
+    )
+    .unwrap();
+    wordlist_metas
+        .iter()
+        .filter_map(|(name, meta)| {
+            let locale = meta.as_ref().ok()?.locale.as_ref()?;
+            Some((name, locale))
+        })
+        .try_for_each(|(name, locale)| {
+            write!(
+                &mut output_file,
+                "{locale:?} => Some({name:?}),",
+                name = name,
+                locale = locale,
             )
         })
         .unwrap();
     write!(&mut output_file, "\t\t_ => None,\n\t}}\n}}\n\n").unwrap()
 }
+
+/// Provenance information about a wordlist, parsed from `# source: ...`,
+/// `# license: ...`, and `# locale: ...` lines in the comments at the top of
+/// the wordlist file.
+#[derive(Debug, Default)]
+struct WordlistMeta {
+    source: Option<String>,
+    license: Option<String>,
+    locale: Option<String>,
+}
+
+impl WordlistMeta {
+    fn from_header(content: &str) -> Self {
+        let mut meta = WordlistMeta::default();
+
+        // Only look at the leading comments; the header ends at the first word
+        let header = content
+            .lines()
+            .map(|line| line.trim())
+            .take_while(|line| line.is_empty() || line.starts_with('#'))
+            .filter_map(|line| line.strip_prefix('#'))
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.trim(), value.trim()));
+
+        for (key, value) in header {
+            if key.eq_ignore_ascii_case("source") {
+                meta.source = Some(value.to_string());
+            } else if key.eq_ignore_ascii_case("license") {
+                meta.license = Some(value.to_string());
+            } else if key.eq_ignore_ascii_case("locale") {
+                meta.locale = Some(value.to_lowercase().replace('-', "_"));
+            }
+        }
+
+        meta
+    }
+}
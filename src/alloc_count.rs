@@ -0,0 +1,35 @@
+//! A global allocator that counts allocations, so that --benchmark can compare
+//! how much different ways of rendering passwords allocate. This is only used
+//! with the `count-allocations` feature, since it adds some overhead to every
+//! allocation.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// The system allocator, but counting each allocation and reallocation
+pub struct CountingAllocator;
+
+// SAFETY: every method defers to the system allocator, which upholds the
+// GlobalAlloc contract.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// The number of allocations (including reallocations) made so far
+pub fn allocations() -> usize {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
@@ -0,0 +1,87 @@
+//! Support for a configuration file providing default values for some options.
+//!
+//! The config file is TOML, with a key for each option it can set. Precedence
+//! is: command line options, then the config file, then the builtin defaults.
+//!
+//! Example:
+//!
+//! ```toml
+//! word_count = 5
+//! wordlist = "english"
+//! newline = "always"
+//! symbol_set = "!@#$%"
+//! default_min_length = 32
+//! ```
+
+use std::env;
+use std::fmt::Display;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde::de::{self, Deserializer};
+use serde::Deserialize;
+
+use crate::{NewlineBehavior, WordlistSelection};
+
+/// The error for a config file that isn't valid TOML, has an unknown key, or
+/// has an invalid value. It includes the line and column of the problem.
+pub type ConfigError = toml::de::Error;
+
+/// Default values for options, loaded from a config file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub word_count: Option<u16>,
+
+    #[serde(default, deserialize_with = "parse")]
+    pub wordlist: Option<WordlistSelection>,
+
+    #[serde(default, deserialize_with = "parse")]
+    pub newline: Option<NewlineBehavior>,
+
+    #[serde(default, deserialize_with = "non_empty")]
+    pub symbol_set: Option<String>,
+
+    pub default_min_length: Option<usize>,
+}
+
+/// Deserialize a string with its `FromStr` implementation, so that the config
+/// file accepts the same values as the command line
+fn parse<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map(Some)
+        .map_err(de::Error::custom)
+}
+
+fn non_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    match String::deserialize(deserializer)? {
+        s if s.is_empty() => Err(de::Error::custom("must not be empty")),
+        s => Ok(Some(s)),
+    }
+}
+
+/// The location of the config file, if no other location was specified. This
+/// is `$XDG_CONFIG_HOME/makepass/config.toml`, falling back to
+/// `~/.config/makepass/config.toml`.
+pub fn default_config_path() -> Option<PathBuf> {
+    let config_dir = match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(config_dir.join("makepass").join("config.toml"))
+}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str(s)
+    }
+}
@@ -0,0 +1,135 @@
+//! Rough entropy estimates for existing passwords, under makepass's model.
+//!
+//! A password is split into words from a wordlist and leftover characters,
+//! choosing the split with the lowest total entropy. Each word contributes
+//! the entropy of choosing it from the wordlist, and each leftover character
+//! contributes the entropy of choosing it from its character class. This is
+//! an upper bound on how well an attacker who knows the wordlist could do;
+//! it doesn't account for other structure, like dictionary words from other
+//! languages or common substitutions.
+
+use std::collections::HashSet;
+
+/// The number of possible characters assumed for a character that isn't an
+/// ASCII letter, digit, or symbol. This is a very rough guess.
+const OTHER_CHARSET_SIZE: usize = 256;
+
+/// The number of ASCII symbols (printable characters that aren't letters or
+/// digits, including space)
+const SYMBOL_CHARSET_SIZE: usize = 33;
+
+/// One piece of an existing password
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment<'a> {
+    /// A word from the wordlist
+    Word(&'a str),
+
+    /// A single character that isn't part of a word
+    Other(char),
+}
+
+/// The result of estimating the entropy of an existing password
+#[derive(Debug, Clone, PartialEq)]
+pub struct Estimate<'a> {
+    pub segments: Vec<Segment<'a>>,
+    pub entropy: f32,
+}
+
+impl<'a> Estimate<'a> {
+    /// Iterate over the words recognized in the password
+    pub fn words(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.segments.iter().filter_map(|segment| match *segment {
+            Segment::Word(word) => Some(word),
+            Segment::Other(_) => None,
+        })
+    }
+
+    /// The number of characters that weren't part of a recognized word
+    pub fn other_count(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|segment| matches!(segment, Segment::Other(_)))
+            .count()
+    }
+}
+
+/// The entropy of a single character, chosen at random from its class
+fn char_entropy(c: char) -> f32 {
+    let charset_size = if c.is_ascii_alphabetic() {
+        26
+    } else if c.is_ascii_digit() {
+        10
+    } else if c.is_ascii() {
+        SYMBOL_CHARSET_SIZE
+    } else {
+        OTHER_CHARSET_SIZE
+    };
+
+    (charset_size as f32).log2()
+}
+
+/// Estimate the entropy of an existing password, recognizing words
+/// (case-insensitively) from the wordlist.
+pub fn estimate<'a>(password: &'a str, wordlist: &[&str]) -> Estimate<'a> {
+    let words: HashSet<String> = wordlist.iter().map(|word| word.to_lowercase()).collect();
+    let word_entropy = (wordlist.len() as f32).log2();
+    let longest_word = wordlist
+        .iter()
+        .map(|word| word.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    // The byte offset of each character, plus the end of the password
+    let offsets: Vec<usize> = password
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(Some(password.len()))
+        .collect();
+
+    // best[i] is the lowest entropy split of the first i characters, and the
+    // number of characters in the last segment of that split.
+    let mut best: Vec<(f32, usize)> = vec![(0.0, 0)];
+
+    for end in 1..offsets.len() {
+        let last_char = password[offsets[end - 1]..offsets[end]]
+            .chars()
+            .next()
+            .expect("every offset starts a character");
+
+        let mut choice = (best[end - 1].0 + char_entropy(last_char), 1);
+
+        for start in end.saturating_sub(longest_word)..end - 1 {
+            let entropy = best[start].0 + word_entropy;
+
+            if entropy < choice.0
+                && words.contains(&password[offsets[start]..offsets[end]].to_lowercase())
+            {
+                choice = (entropy, end - start);
+            }
+        }
+
+        best.push(choice);
+    }
+
+    let mut segments = Vec::new();
+    let mut end = offsets.len() - 1;
+
+    while end > 0 {
+        let start = end - best[end].1;
+        let text = &password[offsets[start]..offsets[end]];
+
+        segments.push(match best[end].1 {
+            1 => Segment::Other(text.chars().next().expect("segment isn't empty")),
+            _ => Segment::Word(text),
+        });
+
+        end = start;
+    }
+
+    segments.reverse();
+
+    Estimate {
+        segments,
+        entropy: best[offsets.len() - 1].0,
+    }
+}
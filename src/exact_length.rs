@@ -0,0 +1,147 @@
+//! Selection of words whose lengths sum to an exact total.
+//!
+//! Rather than generating passwords and rejecting the ones of the wrong
+//! length, we count the number of ways to choose a set of distinct words with
+//! the right total length, and use those counts to sample uniformly from only
+//! the valid sets. Words are grouped into classes by length; for each class,
+//! we decide how many words to take from it, weighted by the number of ways
+//! the remaining classes can complete the set.
+
+use std::collections::BTreeMap;
+
+use rand::seq::SliceRandom;
+use rand::{CryptoRng, Rng};
+
+use crate::util::Encoding;
+
+/// The number of ways to choose k items from n, as a float (because these
+/// numbers can get very large)
+fn binomial(n: usize, k: usize) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * (n - i) as f64 / (i + 1) as f64)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExactLength<'a> {
+    /// The words in the wordlist, grouped by length (shortest first)
+    classes: Vec<(usize, Vec<&'a str>)>,
+
+    /// table[i][j][len] is the number of ways to choose j distinct words, with
+    /// a total length of len, from the first i classes
+    table: Vec<Vec<Vec<f64>>>,
+
+    num_words: usize,
+    words_length: usize,
+}
+
+impl<'a> ExactLength<'a> {
+    /// Prepare to select `num_words` distinct words from `wordlist` with a
+    /// total length of exactly `words_length`. Returns None if there are no
+    /// such combinations of words.
+    pub fn new(
+        wordlist: &[&'a str],
+        num_words: usize,
+        words_length: usize,
+        encoding: Encoding,
+    ) -> Option<Self> {
+        let mut classes: BTreeMap<usize, Vec<&'a str>> = BTreeMap::new();
+
+        wordlist
+            .iter()
+            .map(|&word| (encoding.measure(word), word))
+            .filter(|&(len, _)| len <= words_length)
+            .for_each(|(len, word)| classes.entry(len).or_default().push(word));
+
+        let classes: Vec<_> = classes.into_iter().collect();
+
+        let mut initial = vec![vec![0.0; words_length + 1]; num_words + 1];
+        initial[0][0] = 1.0;
+        let mut table = vec![initial];
+
+        for (class_len, words) in &classes {
+            let prev = table.last().expect("table is never empty");
+            let mut next = vec![vec![0.0; words_length + 1]; num_words + 1];
+
+            for (count, row) in prev.iter().enumerate() {
+                for (len, &ways) in row.iter().enumerate().filter(|&(_, &ways)| ways > 0.0) {
+                    for taken in 0..=words.len().min(num_words - count) {
+                        let total_len = len + taken * class_len;
+
+                        if total_len > words_length {
+                            break;
+                        }
+
+                        next[count + taken][total_len] += ways * binomial(words.len(), taken);
+                    }
+                }
+            }
+
+            table.push(next);
+        }
+
+        let plan = ExactLength {
+            classes,
+            table,
+            num_words,
+            words_length,
+        };
+
+        match plan.total_sets() > 0.0 {
+            true => Some(plan),
+            false => None,
+        }
+    }
+
+    /// The total number of distinct sets of words with the right length
+    fn total_sets(&self) -> f64 {
+        self.table.last().expect("table is never empty")[self.num_words][self.words_length]
+    }
+
+    /// The entropy of the selected words. Each set of words can be arranged in
+    /// num_words! different orders.
+    pub fn entropy(&self) -> f32 {
+        let orderings: f64 = (1..=self.num_words).map(|n| (n as f64).log2()).sum();
+        (self.total_sets().log2() + orderings) as f32
+    }
+
+    pub fn gen_words<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Vec<&'a str> {
+        let mut words = Vec::with_capacity(self.num_words);
+        let mut count = self.num_words;
+        let mut len = self.words_length;
+
+        // Work backwards through the classes, deciding how many words to take
+        // from each one
+        for (i, (class_len, class_words)) in self.classes.iter().enumerate().rev() {
+            let prev = &self.table[i];
+            let mut choice = rng.gen::<f64>() * self.table[i + 1][count][len];
+            let mut taken = 0;
+
+            for candidate in 0..=class_words.len().min(count) {
+                if candidate * class_len > len {
+                    break;
+                }
+
+                let weight = binomial(class_words.len(), candidate)
+                    * prev[count - candidate][len - candidate * class_len];
+
+                if weight > 0.0 {
+                    // Keep track of the last valid candidate, in case of
+                    // floating point rounding errors
+                    taken = candidate;
+
+                    if choice < weight {
+                        break;
+                    }
+
+                    choice -= weight;
+                }
+            }
+
+            words.extend(class_words.choose_multiple(rng, taken).copied());
+            count -= taken;
+            len -= taken * class_len;
+        }
+
+        words.shuffle(rng);
+        words
+    }
+}
@@ -0,0 +1,235 @@
+//! Checks for weak patterns in generated passwords. These are applied to
+//! fully generated passwords, and passwords that fail them are rejected.
+//! Together with the length bounds, they make up the `Constraints` on a
+//! password; the fraction of generated passwords that satisfy all of the
+//! constraints is used to adjust the entropy estimate.
+
+use std::cell::OnceCell;
+use std::fmt::{self, Display, Formatter};
+
+use crate::password::{Component, Password};
+use crate::util::{Bounds, Encoding};
+
+/// A check that generated passwords must pass, in addition to the length
+/// bounds
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Reject passwords containing sequences; see `contains_sequence`
+    NoSequences,
+
+    /// Reject passwords whose words have fewer than this many distinct lengths
+    MinDistinctWordLengths(usize),
+
+    /// Reject passwords starting with a digit
+    NoLeadingDigit,
+
+    /// Reject passwords that don't contain every character class; see
+    /// `has_each_class`
+    EachClass,
+
+    /// Reject passwords without both a short and a long word; see
+    /// `has_word_length_variety`
+    WordLengthVariety,
+
+    /// Reject passwords that don't contain this string anywhere. The check is
+    /// case-sensitive.
+    Contains(String),
+
+    /// Reject passwords with more than this many decorations; see
+    /// `count_decorations`
+    MaxDecorations(usize),
+}
+
+impl Filter {
+    /// Check if a password passes this filter. `rendered` renders the
+    /// password, so that it's only rendered for filters that need it.
+    fn accepts<'r>(
+        &self,
+        password: &Password,
+        encoding: Encoding,
+        rendered: impl FnOnce() -> &'r str,
+    ) -> bool {
+        match *self {
+            Filter::Contains(ref fragment) => rendered().contains(fragment.as_str()),
+            Filter::MaxDecorations(max) => count_decorations(password) <= max,
+            Filter::NoSequences => !contains_sequence(rendered()),
+            Filter::MinDistinctWordLengths(min) => distinct_word_lengths(password, encoding) >= min,
+            Filter::NoLeadingDigit => !starts_with_digit(rendered()),
+            Filter::EachClass => has_each_class(rendered()),
+            Filter::WordLengthVariety => has_word_length_variety(password, encoding),
+        }
+    }
+}
+
+/// Describe a filter, in a format satisfying "passwords that had {}"
+impl Display for Filter {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Filter::NoSequences => f.write_str("no character sequences"),
+            Filter::MinDistinctWordLengths(min) => {
+                write!(f, "at least {} distinct word lengths", min)
+            }
+            Filter::NoLeadingDigit => f.write_str("no leading digit"),
+            Filter::EachClass => {
+                f.write_str("at least one lowercase letter, uppercase letter, digit, and symbol")
+            }
+            Filter::WordLengthVariety => write!(
+                f,
+                "a word of length at most {} and a word of length at least {}",
+                SHORT_WORD_MAX, LONG_WORD_MIN,
+            ),
+            Filter::Contains(ref fragment) => write!(f, "{:?} somewhere in them", fragment),
+            Filter::MaxDecorations(max) => {
+                write!(f, "at most {} numeral digits and symbols in total", max)
+            }
+        }
+    }
+}
+
+/// All of the constraints on generated passwords: the length bounds, plus any
+/// filters.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constraints {
+    pub length: Bounds,
+    pub encoding: Encoding,
+    pub filters: Vec<Filter>,
+}
+
+impl Constraints {
+    /// Check if a password satisfies all of the constraints. The length is
+    /// that of the whole rendered password, including the separators,
+    /// numeral, symbols (at either end), and checksum.
+    pub fn accepts(&self, password: &Password) -> bool {
+        let rendered = OnceCell::new();
+
+        debug_assert_eq!(
+            self.encoding.measure(password),
+            self.encoding
+                .measure(rendered.get_or_init(|| password.to_string()).as_str()),
+            "the measured length of {} doesn't match its rendered length",
+            password,
+        );
+
+        self.length.check_len(password, self.encoding).is_ok()
+            && self.filters.iter().all(|filter| {
+                filter.accepts(password, self.encoding, || {
+                    rendered.get_or_init(|| password.to_string())
+                })
+            })
+    }
+}
+
+/// Describe the constraints, in a format satisfying "passwords that had {}"
+impl Display for Constraints {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "a length of {} {}",
+            self.length.display(),
+            self.encoding.unit_name()
+        )?;
+
+        self.filters
+            .iter()
+            .try_for_each(|filter| write!(f, " and {}", filter))
+    }
+}
+
+/// Count the decorations in a password: each digit of the numeral, and each
+/// symbol (at either end). The separators and checksum aren't counted.
+pub fn count_decorations(password: &Password) -> usize {
+    password
+        .components()
+        .map(|component| match component {
+            Component::Numeral(numeral) => numeral.num_digits(),
+            Component::Symbol(_) => 1,
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Common keyboard rows. Any 3 adjacent keys in a row (in either direction)
+/// are considered a sequence.
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+
+/// The length of the shortest run of characters considered a sequence
+const SEQUENCE_LENGTH: usize = 3;
+
+/// Check if a password contains a run of 3 or more ascending or descending
+/// letters or digits (like "abc" or "321"), or 3 or more adjacent keys on a
+/// keyboard (like "qwe" or "lkj"). The check is case-insensitive.
+pub fn contains_sequence(password: &str) -> bool {
+    let chars: Vec<char> = password.chars().map(|c| c.to_ascii_lowercase()).collect();
+
+    chars.windows(SEQUENCE_LENGTH).any(|window| {
+        is_alphanumeric_run(window) || KEYBOARD_ROWS.iter().any(|row| is_keyboard_run(row, window))
+    })
+}
+
+fn is_alphanumeric_run(window: &[char]) -> bool {
+    let same_class =
+        window.iter().all(|c| c.is_ascii_lowercase()) || window.iter().all(|c| c.is_ascii_digit());
+
+    same_class
+        && (window
+            .windows(2)
+            .all(|pair| pair[0] as u32 + 1 == pair[1] as u32)
+            || window
+                .windows(2)
+                .all(|pair| pair[0] as u32 == pair[1] as u32 + 1))
+}
+
+fn is_keyboard_run(row: &str, window: &[char]) -> bool {
+    let window: String = window.iter().collect();
+    let reversed: String = window.chars().rev().collect();
+
+    row.contains(&window) || row.contains(&reversed)
+}
+
+/// Check if a password starts with a digit, which some legacy systems reject.
+pub fn starts_with_digit(password: &str) -> bool {
+    password.chars().next().is_some_and(|c| c.is_ascii_digit())
+}
+
+/// Check if a password contains at least one lowercase letter, one uppercase
+/// letter, one digit, and one symbol (anything else, other than whitespace),
+/// as many sites require.
+pub fn has_each_class(password: &str) -> bool {
+    password.chars().any(char::is_lowercase)
+        && password.chars().any(char::is_uppercase)
+        && password.chars().any(|c| c.is_ascii_digit())
+        && password
+            .chars()
+            .any(|c| !c.is_alphanumeric() && !c.is_whitespace())
+}
+
+/// The longest a word can be to count as short, for `has_word_length_variety`
+pub const SHORT_WORD_MAX: usize = 4;
+
+/// The shortest a word can be to count as long, for `has_word_length_variety`
+pub const LONG_WORD_MIN: usize = 7;
+
+/// Check if a password has at least one short word (at most SHORT_WORD_MAX
+/// long) and one long word (at least LONG_WORD_MIN long). A mix of short and
+/// long words is easier to remember than words of similar lengths.
+pub fn has_word_length_variety(password: &Password, encoding: Encoding) -> bool {
+    let lengths = || password.words().map(|word| encoding.measure(word));
+
+    lengths().any(|length| length <= SHORT_WORD_MAX)
+        && lengths().any(|length| length >= LONG_WORD_MIN)
+}
+
+/// Count the number of distinct lengths among the words in a password.
+pub fn distinct_word_lengths(password: &Password, encoding: Encoding) -> usize {
+    let mut lengths: Vec<usize> = password
+        .components()
+        .filter_map(|component| match component {
+            Component::Word(word) => Some(encoding.measure(word)),
+            _ => None,
+        })
+        .collect();
+
+    lengths.sort_unstable();
+    lengths.dedup();
+    lengths.len()
+}
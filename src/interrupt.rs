@@ -0,0 +1,43 @@
+//! Graceful handling of Ctrl-C. Rather than being killed partway through
+//! writing a password, makepass notes the interrupt, and the loops that
+//! generate many passwords (like -n and --benchmark) stop at the next
+//! opportunity, so that any buffered output is flushed before exiting.
+//!
+//! This requires the `interrupt` feature, and is only supported on unix;
+//! otherwise, Ctrl-C kills makepass as usual. The handler is a plain
+//! `signal` call through libc, which makepass already depends on for
+//! --wrap, rather than the ctrlc crate: all it needs is to set a flag, and
+//! ctrlc's extra thread and Windows support would be more than that.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Check if Ctrl-C has been pressed since `install` was called
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
+
+#[cfg(all(unix, feature = "interrupt"))]
+extern "C" fn handle_sigint(_signal: libc::c_int) {
+    // Only async-signal-safe operations are allowed here; an atomic store is
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+/// Install the Ctrl-C handler. After this, Ctrl-C no longer kills makepass,
+/// so every loop that can run for a long time must check `interrupted`;
+/// `sample_passwords` does so for every attempt.
+#[cfg(all(unix, feature = "interrupt"))]
+pub fn install() {
+    // SAFETY: handle_sigint is async-signal-safe, and has the signature that
+    // signal expects.
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            handle_sigint as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        );
+    }
+}
+
+#[cfg(not(all(unix, feature = "interrupt")))]
+pub fn install() {}
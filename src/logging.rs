@@ -0,0 +1,80 @@
+//! Leveled diagnostic logging, for --log-level. Log messages describe what
+//! makepass is doing internally (loading the wordlist, sampling passwords,
+//! and so on), and go to stderr, separately from the entropy and count
+//! reports.
+//!
+//! Messages are only written with the `logging` feature; without it, the
+//! logging macros check nothing and write nothing. So anything the user needs
+//! to see, like a warning that passwords will be weak, isn't logged: it's
+//! always written to stderr with eprintln!.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The severity of a log message. A message is written if its level is at
+/// most the level set with `set_level`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+named_enum! {
+    pub Level, InvalidLevel("log level") {
+        "off" => Off,
+        "error" => Error,
+        "warn" => Warn,
+        "info" => Info,
+        "debug" => Debug,
+        "trace" => Trace,
+    }
+}
+
+impl Level {
+    /// The name of this level. The names are in the same order as the
+    /// variants, so this is just an index.
+    pub fn name(self) -> &'static str {
+        Level::NAMES[self as usize]
+    }
+}
+
+static LEVEL: AtomicU8 = AtomicU8::new(Level::Info as u8);
+
+/// Set the most verbose level of message that will be written
+pub fn set_level(level: Level) {
+    LEVEL.store(level as u8, Ordering::Relaxed);
+}
+
+/// Check if messages at this level will be written. This can be used to skip
+/// expensive work that's only needed for a log message.
+#[cfg(feature = "logging")]
+pub fn enabled(level: Level) -> bool {
+    level != Level::Off && level as u8 <= LEVEL.load(Ordering::Relaxed)
+}
+
+#[cfg(not(feature = "logging"))]
+pub fn enabled(_level: Level) -> bool {
+    false
+}
+
+/// Write a message at the given level, if that level is enabled
+macro_rules! log {
+    ($level:expr, $($arg:tt)*) => {{
+        let level = $level;
+
+        if $crate::logging::enabled(level) {
+            eprintln!("[{}] {}", level.name(), format_args!($($arg)*));
+        }
+    }};
+}
+
+macro_rules! info {
+    ($($arg:tt)*) => { log!($crate::logging::Level::Info, $($arg)*) };
+}
+
+macro_rules! debug {
+    ($($arg:tt)*) => { log!($crate::logging::Level::Debug, $($arg)*) };
+}
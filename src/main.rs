@@ -1,27 +1,66 @@
-mod password;
+#[macro_use]
 mod util;
+#[macro_use]
+mod logging;
+#[macro_use]
+mod term;
+
+#[cfg(feature = "count-allocations")]
+mod alloc_count;
+mod config;
+mod estimate;
+mod exact_length;
+mod filters;
+mod interrupt;
+mod latin;
+mod password;
+mod phonetic;
+mod profanity;
+mod pronounceable;
+mod self_test;
+mod spec;
+mod template;
+mod word_weights;
 mod wordlists;
 
 use std::{
     cmp::{max, min},
+    collections::{BTreeMap, HashMap, HashSet},
+    env,
+    fmt::{self, Display, Write as _},
+    fs,
     io::{self, Write},
-    iter::FromIterator,
+    iter::{self, FromIterator},
+    path::{Path, PathBuf},
     process::exit,
     str::FromStr,
+    time::{Duration, Instant},
 };
 
-use rand::{rngs::StdRng, SeedableRng};
-use structopt::StructOpt;
+use lazy_format::lazy_format;
+use rand::{rngs::OsRng, seq::SliceRandom, CryptoRng, Rng, SeedableRng};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use structopt::{clap::ArgMatches, StructOpt};
 use thiserror::Error;
 
-use crate::password::PasswordRules;
-use crate::util::Bounds;
+use crate::config::{default_config_path, Config, ConfigError};
+use crate::filters::{Constraints, Filter, LONG_WORD_MIN, SHORT_WORD_MAX};
+use crate::logging::Level;
+use crate::password::{
+    split_symbols, Component, Password, PasswordRules, RulesError, Separator, SymbolPosition,
+    WordCase, WordSource,
+};
+use crate::profanity::Blocklist;
+use crate::spec::PasswordSpec;
+use crate::template::{Placeholder, Template};
 use crate::util::Len;
-use crate::wordlists::{WordlistStorage, WORDLIST_NAMES};
-
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
-#[error("Invalid pattern for newline behavior")]
-struct InvalidNewlineBehavior;
+use crate::util::{display_duration, Bounds, Encoding, InvalidBoundsError};
+use crate::word_weights::WordWeights;
+use crate::wordlists::{
+    is_denied, locale_wordlist, wordlist_alias, wordlist_meta, Wordlist, WordlistFileError,
+    WordlistStorage, WORDLIST_NAMES,
+};
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
 enum NewlineBehavior {
@@ -30,6 +69,14 @@ enum NewlineBehavior {
     Auto,
 }
 
+named_enum! {
+    NewlineBehavior, InvalidNewlineBehavior("newline behavior") {
+        "never" => Never,
+        "always" => Always,
+        "auto" => Auto,
+    }
+}
+
 impl NewlineBehavior {
     fn should_print_newline(self) -> bool {
         match self {
@@ -40,25 +87,248 @@ impl NewlineBehavior {
     }
 }
 
-impl FromStr for NewlineBehavior {
-    type Err = InvalidNewlineBehavior;
+/// How generated passwords are written to stdout, for --format
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum OutputFormat {
+    /// Just the passwords, one per line
+    Plain,
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.eq_ignore_ascii_case("never") {
-            Ok(NewlineBehavior::Never)
-        } else if s.eq_ignore_ascii_case("always") {
-            Ok(NewlineBehavior::Always)
-        } else if s.eq_ignore_ascii_case("auto") {
-            Ok(NewlineBehavior::Auto)
-        } else {
-            Err(InvalidNewlineBehavior)
+    /// A header row, then a row of comma-separated values for each password
+    Csv,
+}
+
+named_enum! {
+    OutputFormat, InvalidOutputFormat("output format") {
+        "plain" => Plain,
+        "csv" => Csv,
+    }
+}
+
+/// The header row for --format csv
+const CSV_HEADER: [&str; 4] = ["password", "entropy", "length_bytes", "word_count"];
+
+/// A preset combination of separator and capitalization, for --style
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Style {
+    /// lowercase words run together: "correcthorsebattery"
+    Lower,
+
+    /// lowercase words separated by hyphens: "correct-horse-battery"
+    Kebab,
+
+    /// lowercase words separated by underscores: "correct_horse_battery"
+    Snake,
+
+    /// capitalized words run together, except the first: "correctHorseBattery"
+    Camel,
+
+    /// capitalized words run together: "CorrectHorseBattery"
+    Pascal,
+
+    /// capitalized words separated by spaces: "Correct Horse Battery"
+    Title,
+}
+
+named_enum! {
+    Style, InvalidStyle("style") {
+        "lower" => Lower,
+        "kebab" => Kebab,
+        "snake" => Snake,
+        "camel" => Camel,
+        "pascal" => Pascal,
+        "title" => Title,
+    }
+}
+
+impl Style {
+    /// The separator and capitalization for this style. A separator of None
+    /// means the words are run together.
+    fn resolve(self) -> (Option<&'static str>, WordCase) {
+        match self {
+            Style::Lower => (None, WordCase::Lower),
+            Style::Kebab => (Some("-"), WordCase::Lower),
+            Style::Snake => (Some("_"), WordCase::Lower),
+            Style::Camel => (None, WordCase::Camel),
+            Style::Pascal => (None, WordCase::Title),
+            Style::Title => (Some(" "), WordCase::Title),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
+#[error("The guess rate must be a positive number")]
+struct InvalidGuessRate;
+
+fn parse_guess_rate(s: &str) -> Result<f64, InvalidGuessRate> {
+    match s.trim().parse() {
+        Ok(rate) if rate > 0.0 && f64::is_finite(rate) => Ok(rate),
+        _ => Err(InvalidGuessRate),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
+#[error("The numeral base must be a number from 2 to 36")]
+struct InvalidNumeralBase;
+
+fn parse_numeral_base(s: &str) -> Result<u8, InvalidNumeralBase> {
+    match s.trim().parse() {
+        Ok(base @ 2..=36) => Ok(base),
+        _ => Err(InvalidNumeralBase),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
+#[error("The word separator must not be empty")]
+struct InvalidWordSeparator;
+
+fn parse_word_separator(s: &str) -> Result<String, InvalidWordSeparator> {
+    match s.is_empty() {
+        true => Err(InvalidWordSeparator),
+        false => Ok(s.to_string()),
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
+#[error("The separator set must not be empty")]
+struct InvalidSeparatorSet;
+
+/// Parse a --separator-set. Repeated characters are removed, so that every
+/// separator is equally likely, and each one only counts once towards the
+/// entropy.
+fn parse_separator_set(s: &str) -> Result<String, InvalidSeparatorSet> {
+    let mut separators = String::new();
+
+    for c in s.chars() {
+        if !separators.contains(c) {
+            separators.push(c);
         }
     }
+
+    match separators.is_empty() {
+        true => Err(InvalidSeparatorSet),
+        false => Ok(separators),
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
-#[error("Invalid wordlist selection")]
-struct InvalidWordlistSelection;
+#[error("The charset must contain at least 2 distinct characters")]
+struct InvalidCharset;
+
+/// The characters to choose from with --charset. Repeated characters are
+/// removed, so that every character is equally likely.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Charset(Vec<char>);
+
+impl FromStr for Charset {
+    type Err = InvalidCharset;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars: Vec<char> = Vec::new();
+
+        for c in s.chars() {
+            if !chars.contains(&c) {
+                chars.push(c);
+            }
+        }
+
+        match chars.len() {
+            0 | 1 => Err(InvalidCharset),
+            _ => Ok(Charset(chars)),
+        }
+    }
+}
+
+impl Charset {
+    /// Generate a string of `length` characters, each chosen uniformly from
+    /// the charset
+    fn gen_string<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R, length: usize) -> String {
+        (0..length)
+            .map(|_| *self.0.choose(rng).expect("charset isn't empty"))
+            .collect()
+    }
+
+    /// The entropy of a string of `length` characters from the charset
+    fn entropy(&self, length: usize) -> f32 {
+        length as f32 * (self.0.len() as f32).log2()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Error)]
+#[error("Invalid seed {input:?}; must be a number, or 64 hex digits")]
+struct InvalidSeed {
+    input: String,
+}
+
+/// A seed for the random number generator, from --seed
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Seed {
+    /// A short seed, which is expanded into a full one. Easy to type, but
+    /// also easy to guess.
+    Number(u64),
+
+    /// A full 256-bit seed, written as 64 hex digits
+    Full([u8; 32]),
+}
+
+impl FromStr for Seed {
+    type Err = InvalidSeed;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidSeed {
+            input: s.to_string(),
+        };
+
+        if let Ok(seed) = s.parse() {
+            return Ok(Seed::Number(seed));
+        }
+
+        if s.len() != 64 || !s.is_ascii() {
+            return Err(invalid());
+        }
+
+        let mut bytes = [0; 32];
+
+        for (byte, pair) in bytes.iter_mut().zip(s.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+
+        Ok(Seed::Full(bytes))
+    }
+}
+
+impl Display for Seed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Seed::Number(seed) => seed.fmt(f),
+            Seed::Full(bytes) => bytes.iter().try_for_each(|byte| write!(f, "{:02x}", byte)),
+        }
+    }
+}
+
+/// The minimum password length, if there's no --min-length (or
+/// --default-min-length). If there's a --max-length, it's used instead if
+/// it's lower.
+const DEFAULT_MIN_LENGTH: usize = 24;
+
+/// The symbols to choose from with --append-symbol, if there's no --symbol-set
+const DEFAULT_SYMBOL_SET: &str = "!\"#$%&'()*+,-./\\:;<=>?@[]^_`{|}~";
+
+/// The environment variable that sets the default wordlist; see --wordlist
+const WORDLIST_ENV_VAR: &str = "MAKEPASS_WORDLIST";
+
+/// The names that select reading the wordlist from stdin
+const STDIN_WORDLIST_NAMES: &[&str] = &["stdin", "-"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Error)]
+#[error(
+    "Invalid wordlist selection {input:?}; must be one of: {}, {}",
+    WORDLIST_NAMES.join(", "),
+    STDIN_WORDLIST_NAMES.join(", ")
+)]
+struct InvalidWordlistSelection {
+    input: String,
+}
 
 #[derive(Debug, Clone, Eq, PartialEq)]
 enum WordlistSelection {
@@ -72,16 +342,73 @@ impl FromStr for WordlistSelection {
     fn from_str(mut s: &str) -> Result<Self, Self::Err> {
         s = s.trim();
 
-        if s.eq_ignore_ascii_case("stdin") || s == "-" {
+        if STDIN_WORDLIST_NAMES
+            .iter()
+            .any(|name| s.eq_ignore_ascii_case(name))
+        {
             Ok(WordlistSelection::Stdin)
         } else if s.is_empty() {
-            Err(InvalidWordlistSelection)
+            Err(InvalidWordlistSelection {
+                input: s.to_string(),
+            })
         } else {
             Ok(WordlistSelection::Named(s.to_lowercase()))
         }
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
+#[error("Invalid pattern; must be a comma-separated list of category names")]
+struct InvalidWordPattern;
+
+/// A list of wordlist categories, one for each word in the password
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct WordPattern(Vec<String>);
+
+impl FromStr for WordPattern {
+    type Err = InvalidWordPattern;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(|category| category.trim())
+            .map(|category| match category.is_empty() {
+                true => Err(InvalidWordPattern),
+                false => Ok(category.to_string()),
+            })
+            .collect::<Result<_, _>>()
+            .map(WordPattern)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Error)]
+#[error("Invalid fixed word; must be POSITION:WORD, where POSITION starts at 1")]
+struct InvalidFixedWord;
+
+/// A word that's always placed at a particular position in the password
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct FixedWord {
+    /// The 1-based position of the word
+    position: usize,
+    word: String,
+}
+
+impl FromStr for FixedWord {
+    type Err = InvalidFixedWord;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (position, word) = s.split_once(':').ok_or(InvalidFixedWord)?;
+        let word = word.trim();
+
+        match position.trim().parse() {
+            Ok(position) if position > 0 && !word.is_empty() => Ok(FixedWord {
+                position,
+                word: word.to_string(),
+            }),
+            _ => Err(InvalidFixedWord),
+        }
+    }
+}
+
 /// Help text
 #[derive(Debug, Clone, StructOpt)]
 #[structopt(
@@ -90,23 +417,157 @@ impl FromStr for WordlistSelection {
     raw(setting = "structopt::clap::AppSettings::UnifiedHelpMessage")
 )]
 struct Opt {
-    /// The number of words in the password
+    /// The number of words in the password.
+    ///
+    /// With 0 words, the password is just the numeral, symbols, and checksum (whichever are
+    /// enabled), and the minimum length defaults to 0.
     #[structopt(short = "c", long, default_value = "4")]
     word_count: u16,
 
-    /// The maximum length of the password, in bytes.
+    /// Set the number of words, numeral, and symbols from a compact spec, like 4w+n+s.
+    ///
+    /// The spec is a word count followed by "w", then optionally "+n" for a numeral and "+s"
+    /// for a symbol (or "+3s" for 3 symbols). For instance, 5w+2s is 5 words and 2 symbols,
+    /// with no numeral. The wordlist, separator, and symbol set are still chosen with the
+    /// other options.
+    #[structopt(
+        long,
+        value_name = "SPEC",
+        conflicts_with = "word-count",
+        conflicts_with = "pattern",
+        conflicts_with = "append-numeral",
+        conflicts_with = "no-append-numeral",
+        conflicts_with = "append-symbol",
+        conflicts_with = "no-append-symbol",
+        conflicts_with = "symbol-count"
+    )]
+    spec: Option<PasswordSpec>,
+
+    /// Print the compact spec (see --spec) for the other options, then exit
+    #[structopt(long)]
+    print_spec: bool,
+
+    /// Generate a random string of characters from CHARS, instead of a password made of words.
+    ///
+    /// Each character is chosen uniformly from CHARS, which must have at least 2 distinct
+    /// characters. The length is given by --char-length, or --max-length. This is for systems
+    /// that reject passphrases; wordlist options, and options that add numerals, symbols, or
+    /// separators, are ignored.
+    #[structopt(long, value_name = "CHARS")]
+    charset: Option<Charset>,
+
+    /// The number of characters to generate with --charset, at least 1. Defaults to
+    /// MAX_LENGTH.
+    #[structopt(long, value_name = "LENGTH")]
+    char_length: Option<usize>,
+
+    /// The number of passwords to generate, one per line
+    #[structopt(short = "n", long, default_value = "1", value_name = "COUNT")]
+    num_passwords: usize,
+
+    /// Write each password to its own file in this directory, rather than to stdout.
+    ///
+    /// The files are named password-001.txt, password-002.txt, and so on, zero-padded so
+    /// that they sort in order. On unix, they're only readable by their owner. On Windows,
+    /// they're hidden and can't be opened by anyone else while they're being written, but
+    /// they inherit the directory's permissions, so this is an error unless
+    /// --allow-unrestricted-files is also given. The directory must already exist, and
+    /// existing files are never overwritten.
+    #[structopt(
+        long,
+        value_name = "DIR",
+        parse(from_os_str),
+        conflicts_with = "json-lines"
+    )]
+    output_dir: Option<PathBuf>,
+
+    /// Allow --output-dir on platforms where password files can't be made readable only by
+    /// their owner, like Windows.
+    ///
+    /// The files then have the same permissions as the directory, so only use this with a
+    /// directory that only you can read.
+    #[structopt(long, requires = "output-dir")]
+    allow_unrestricted_files: bool,
+
+    /// The maximum number of times any word may be repeated across the generated passwords.
+    ///
+    /// With -n, passwords that would use a word more than this many times in addition to
+    /// its first use are discarded and regenerated, so 0 means that no word appears in more
+    /// than one password. Very low values with many passwords can exhaust the wordlist, in
+    /// which case generation fails.
+    #[structopt(long, value_name = "N")]
+    max_word_repeats: Option<usize>,
+
+    /// With -n, don't print the same passphrase more than once.
+    ///
+    /// Passwords are compared ignoring the order and case of their words, their separators,
+    /// and which end their symbols are on, so a password that's a reordering of an earlier
+    /// one is discarded and regenerated. This matters most with --sort-words or a small
+    /// wordlist. If the passwords run out, generation fails.
+    #[structopt(long)]
+    unique: bool,
+
+    /// The maximum length of the password, in bytes (or characters; see --encoding).
     ///
     /// Defaults to unlimited.
     #[structopt(short = "l", long, value_name = "MAX_LENGTH")]
     max_length: Option<usize>,
 
-    /// The minimum length of the password, in bytes.
+    /// The minimum length of the password, in bytes (or characters; see --encoding).
     ///
-    /// Defaults to 24, or MAX_LENGTH, whichever is lower
+    /// Defaults to 24 (or --default-min-length), or MAX_LENGTH, whichever is lower. With
+    /// --word-count 0, it defaults to 0.
     #[structopt(short = "m", long, value_name = "MIN_LENGTH")]
     min_length: Option<usize>,
 
-    /// Append a random numeral (0-9) to the password. This is the default.
+    /// The minimum password length to use when --min-length isn't given. Defaults to 24.
+    ///
+    /// This is meant to be set in the config file (as default_min_length), to change the
+    /// default policy, while still letting --max-length lower it.
+    #[structopt(long, value_name = "LENGTH")]
+    default_min_length: Option<usize>,
+
+    /// The exact length of the password, in bytes (or characters; see --encoding).
+    ///
+    /// Rather than generating passwords and discarding the ones of the wrong length, words
+    /// are selected such that the password will have exactly this length. If that's not
+    /// possible (for instance, because the symbols in the symbol set have different
+    /// lengths), passwords of the wrong length are discarded, as with --min-length and
+    /// --max-length.
+    #[structopt(
+        long,
+        value_name = "LENGTH",
+        conflicts_with = "min-length",
+        conflicts_with = "max-length"
+    )]
+    exact_length: Option<usize>,
+
+    /// A string to put between each pair of words in the password.
+    ///
+    /// Defaults to nothing, so that the words are run together. The separator is always the
+    /// same, so it adds no entropy.
+    #[structopt(
+        long,
+        value_name = "SEP",
+        conflicts_with = "separator-set",
+        raw(allow_hyphen_values = "true")
+    )]
+    separator: Option<String>,
+
+    /// A set of characters to choose from at random for each gap between words, like "-._".
+    ///
+    /// Each gap gets its own random separator, which adds to the entropy of the password.
+    /// Characters given more than once are only counted once.
+    #[structopt(
+        long,
+        value_name = "SEPARATORS",
+        parse(try_from_str = "parse_separator_set"),
+        raw(allow_hyphen_values = "true")
+    )]
+    separator_set: Option<String>,
+
+    /// Append a random numeral (0-9, or see --numeral-base) to the password. This is the
+    /// default.
     ///
     /// Overridden by --no-append-numeral
     #[structopt(long)]
@@ -119,6 +580,18 @@ struct Opt {
     #[structopt(short = "N", long, overrides_with = "append-numeral")]
     no_append_numeral: bool,
 
+    /// The base of the appended numeral, from 2 to 36.
+    ///
+    /// For instance, 16 appends a random hex digit (0-9 or a-f). Digits past 9 are
+    /// lowercase letters.
+    #[structopt(
+        long,
+        value_name = "BASE",
+        default_value = "10",
+        parse(try_from_str = "parse_numeral_base")
+    )]
+    numeral_base: u8,
+
     /// Append a random special character to the password.
     ///
     /// Overridden by `--no-append-symbol`. See --symbol-set for the default set of special
@@ -130,23 +603,159 @@ struct Opt {
     ///
     /// Overridden by --append-symbol and/or --symbol-set.
     #[structopt(long, overrides_with = "append_symbol")]
-    #[allow(dead_code)] // We only look at append_symbol
     no_append_symbol: bool,
 
     /// The set of symbols to choose from when appending a random symbol.
     ///
     /// Implies --append_symbol. Defaults to !"#$%&'()*+,-./\:;<=>?@[]^_`{|}~. If invoking
-    /// from the shell, make sure to properly escape your symbols.
+    /// from the shell, make sure to properly escape your symbols. Symbols may be emoji; each
+    /// grapheme (including emoji made of several code points, like flags) is one symbol.
     #[structopt(short, long, value_name = "SYMBOLS")]
     symbol_set: Option<String>,
 
-    /// The minimum length of each individual word in the password, in bytes.
+    /// Add these symbols to the symbol set. May be given more than once.
+    ///
+    /// The symbols are added to --symbol-set if it's given, or to the default set otherwise.
+    /// Symbols that are already in the set are ignored, so they don't add any entropy.
+    /// Implies --append-symbol.
+    #[structopt(long, value_name = "SYMBOLS", number_of_values = 1)]
+    symbol_add: Vec<String>,
+
+    /// The number of random special characters to append.
+    ///
+    /// Implies --append-symbol. Each symbol is chosen independently from the symbol set, so
+    /// they may repeat. Defaults to 1.
+    #[structopt(long, value_name = "N")]
+    symbol_count: Option<usize>,
+
+    /// Where to put the random special characters: at the "start" of the password, the "end"
+    /// (the default), or "both".
+    ///
+    /// With "both", --symbol-count symbols go at each end, so there are twice as many in
+    /// total (and twice the entropy from symbols). Implies --append-symbol.
+    #[structopt(
+        long,
+        value_name = "POSITION",
+        raw(possible_values = "SymbolPosition::NAMES")
+    )]
+    symbol_position: Option<SymbolPosition>,
+
+    /// Append a checksum character (0-9 or a-z) to the password.
+    ///
+    /// The checksum is computed from the rest of the password, so it can be used to catch
+    /// typos when the password is entered, but it adds no entropy. It counts towards the
+    /// password length.
+    #[structopt(long)]
+    checksum: bool,
+
+    /// Sort the words in the password alphabetically.
+    ///
+    /// Some people find sorted words easier to remember. This costs entropy, since the order
+    /// of the words no longer carries any information: with 4 words, about 4.58 bits (log2
+    /// of 4!) are lost, and with 6 words, about 9.49 bits. Words fixed with --fixed-word stay
+    /// in their positions.
+    #[structopt(long)]
+    sort_words: bool,
+
+    /// Randomly capitalize each letter of the words in the password.
+    ///
+    /// Each letter adds one bit of entropy, so the total depends on the length of the words;
+    /// it's included in the estimate for each password, but not in --explain or --count-only.
+    /// Passwords like this are much harder to type and remember.
+    #[structopt(long)]
+    random_case: bool,
+
+    /// Convert the words in the password to uppercase.
+    ///
+    /// This is for systems that require all-caps passwords. It doesn't change the entropy of
+    /// the words (an attacker can simply guess them in uppercase), but it can't be combined
+    /// with --random-case, since it would undo the entropy that adds. The numeral (in bases
+    /// above 10) and checksum are always lowercase, and the symbols are unaffected.
+    #[structopt(long, conflicts_with = "random-case", conflicts_with = "all-lower")]
+    all_upper: bool,
+
+    /// Convert the words in the password to lowercase.
+    ///
+    /// This is for case-insensitive systems. As with --all-upper, it doesn't change the
+    /// entropy of the words, and it can't be combined with --random-case.
+    #[structopt(long, conflicts_with = "random-case")]
+    all_lower: bool,
+
+    /// Set the separator and capitalization of the words to a preset style.
+    ///
+    /// "lower" runs lowercase words together, "kebab" separates them with hyphens, and "snake"
+    /// with underscores. "camel" and "pascal" capitalize each word and run them together,
+    /// except that "camel" leaves the first word lowercase. "title" capitalizes each word and
+    /// separates them with spaces. None of these change the entropy of the words.
+    #[structopt(
+        long,
+        value_name = "STYLE",
+        raw(possible_values = "Style::NAMES", case_insensitive = "true"),
+        conflicts_with = "separator",
+        conflicts_with = "separator-set",
+        conflicts_with = "random-case",
+        conflicts_with = "all-upper",
+        conflicts_with = "all-lower"
+    )]
+    style: Option<Style>,
+
+    /// Use as many words as fit in MAX_LENGTH, instead of a fixed number of words.
+    ///
+    /// Words are added one at a time until the next one would make the password longer than
+    /// MAX_LENGTH, so the number of words (and the entropy) varies from password to password;
+    /// the entropy estimate is reported for each one. This is useful for fitting a password
+    /// into a field with a length limit, without the rejections that a tight --max-length
+    /// would otherwise cause. --word-count is ignored.
+    #[structopt(
+        long,
+        requires = "max-length",
+        conflicts_with = "exact-length",
+        conflicts_with = "fixed-word"
+    )]
+    fit_length: bool,
+
+    /// Choose shorter words more often, so that more passwords fit in MAX_LENGTH.
+    ///
+    /// Each word is weighted by how much shorter it is than the longest word in the wordlist,
+    /// so fewer passwords are rejected for being too long. Because the words are no longer
+    /// equally likely, this costs some entropy, which is accounted for in the estimate. By
+    /// default, this is enabled when the words of a typical password wouldn't fit in
+    /// MAX_LENGTH. It only applies to plain wordlists: not with --pattern, --theme-wordlist,
+    /// --pronounceable, or --exact-length.
+    ///
+    /// Overridden by --no-prefer-short
+    #[structopt(long, overrides_with = "no-prefer-short")]
+    prefer_short: bool,
+
+    /// Always choose words uniformly, even when MAX_LENGTH is tight.
+    ///
+    /// Overridden by --prefer-short
+    #[structopt(long, overrides_with = "prefer-short")]
+    no_prefer_short: bool,
+
+    /// How to measure the lengths of words and passwords: "bytes" or "chars".
+    ///
+    /// With "bytes" (the default), lengths are measured in UTF-8 bytes. With "chars", they're
+    /// measured in unicode scalar values, which is usually more intuitive for wordlists
+    /// containing non-ASCII words. This applies to --min-length, --max-length, --min-word,
+    /// and --max-word.
+    #[structopt(
+        long,
+        default_value = "bytes",
+        raw(possible_values = "Encoding::NAMES"),
+        value_name = "UNIT"
+    )]
+    encoding: Encoding,
+
+    /// The minimum length of each individual word in the password, in bytes (or characters;
+    /// see --encoding).
     ///
     /// Defaults to 4, or MAX_WORD, whichever is lower.
     #[structopt(long, value_name = "MIN_WORD_LENGTH")]
     min_word: Option<usize>,
 
-    /// The maximum length of each individual word in the password, in bytes.
+    /// The maximum length of each individual word in the password, in bytes (or characters;
+    /// see --encoding).
     ///
     /// Defaults to 8, or MIN_WORD, whichever is higher.
     #[structopt(long, value_name = "MAX_WORD_LENGTH")]
@@ -156,19 +765,138 @@ struct Opt {
     ///
     /// See --list-wordlist for a list of all available wordlists, and --print-wordlist
     /// for all the words in a given wordlist. This option will also accept "stdin" or "-",
-    /// in which case the words will be read (whitespace-separated) from stdin.
+    /// in which case the words will be read (whitespace-separated) from stdin. If this isn't
+    /// given, the MAKEPASS_WORDLIST environment variable is used, then the config file.
     #[structopt(
         short,
         long,
         value_name = "WORDLIST",
         default_value = "default",
         raw(possible_values = "WORDLIST_NAMES"),
-        possible_value = "stdin",
-        possible_value = "-"
+        raw(possible_values = "STDIN_WORDLIST_NAMES")
     )]
     wordlist: WordlistSelection,
 
-    /// Print the list of available wordlists to stdout, then exit
+    /// Use the builtin wordlist for this locale (like "de" or "en_US"), if --wordlist isn't
+    /// given.
+    ///
+    /// Defaults to the locale in $LANG; if there's no wordlist for that locale, the default
+    /// wordlist is used instead. It's an error if there's no wordlist for a locale given
+    /// with --locale. A wordlist set in the config file takes precedence over $LANG, but not
+    /// over --locale.
+    #[structopt(long, value_name = "LOCALE")]
+    locale: Option<String>,
+
+    /// Read the wordlist from a file, instead of using a builtin wordlist.
+    ///
+    /// The file has the same format as the builtin wordlists: one word per line, ignoring
+    /// blank lines and lines starting with #. Comments may also follow a word on the same
+    /// line, like `Word  # rank:3`. If the file has a .toml extension, it's
+    /// instead read as a categorized wordlist, where each category is an array of words
+    /// (for instance, `noun = ["Cat", "Dog"]`); see --pattern.
+    #[structopt(
+        long,
+        value_name = "PATH",
+        parse(from_os_str),
+        conflicts_with = "wordlist"
+    )]
+    wordlist_file: Option<PathBuf>,
+
+    /// The separator between words in a wordlist read from stdin or --wordlist-file.
+    ///
+    /// Defaults to a newline, so that there's one word per line. Leading and trailing
+    /// whitespace is removed from each word, and empty words are ignored, so a separator
+    /// like "," allows wordlists of comma-separated phrases, which may contain spaces.
+    #[structopt(long, value_name = "SEP", parse(try_from_str = "parse_word_separator"))]
+    word_separator: Option<String>,
+
+    /// Compose accented Latin letters in a wordlist read from stdin or --wordlist-file.
+    ///
+    /// Accented letters can be written either as a single character or as a letter followed
+    /// by a combining accent. These look the same, but have different lengths in bytes and
+    /// characters, and aren't recognized as duplicates. With this option, Latin letters and
+    /// the combining accents right after them are composed into single characters where
+    /// possible. This matches Unicode NFC for most Latin text, but it isn't full NFC: other
+    /// scripts are left alone, and accents aren't reordered. By default, words are used
+    /// exactly as they're written.
+    #[structopt(long)]
+    compose_latin: bool,
+
+    /// Forbid the builtin wordlists; the wordlist must come from stdin or --wordlist-file.
+    ///
+    /// This guarantees that only the words in a list you provide are used, which makes the
+    /// entropy estimate verifiable by anyone with a copy of that list. With this option, a
+    /// --wordlist other than stdin is an error.
+    #[structopt(long)]
+    words_from_stdin_only: bool,
+
+    /// A comma-separated list of categories, like adj,noun,verb,noun.
+    ///
+    /// Requires a categorized wordlist (see --wordlist-file). Each word in the password is
+    /// chosen from the corresponding category, and the number of words is the length of the
+    /// pattern (--word-count is ignored).
+    #[structopt(long, value_name = "PATTERN", conflicts_with = "pronounceable")]
+    pattern: Option<WordPattern>,
+
+    /// Choose some of the words from this wordlist (a built-in wordlist, or a path to a
+    /// file), and the rest from the main wordlist.
+    ///
+    /// This is for themed or branded passwords. The theme words come first, and are taken
+    /// out of the main wordlist so that they can't repeat. They aren't filtered by word
+    /// length. See --theme-count.
+    #[structopt(
+        long,
+        value_name = "NAME_OR_PATH",
+        conflicts_with = "pattern",
+        conflicts_with = "pronounceable"
+    )]
+    theme_wordlist: Option<String>,
+
+    /// The number of words to choose from --theme-wordlist. Defaults to 1.
+    #[structopt(long, value_name = "N", requires = "theme-wordlist")]
+    theme_count: Option<usize>,
+
+    /// Always put WORD at POSITION in the password (counting from 1), like 3:Github.
+    ///
+    /// May be given more than once. The other words are chosen randomly as usual, and the
+    /// fixed words count towards --word-count. Fixed words are assumed to be known to an
+    /// attacker, so they add no entropy, and they make the password less secure than one
+    /// of the same length with only random words. Never use a secret, or anything derived
+    /// from one, as a fixed word.
+    #[structopt(long, value_name = "POSITION:WORD", raw(number_of_values = "1"))]
+    fixed_word: Vec<FixedWord>,
+
+    /// Remove offensive words from the wordlist, using a builtin blocklist.
+    ///
+    /// Words are matched case-insensitively. See --profanity-file to use a custom blocklist.
+    #[structopt(long)]
+    profanity_filter: bool,
+
+    /// Use a custom blocklist file for the profanity filter. Implies --profanity-filter.
+    ///
+    /// The file has one pattern per line; blank lines and lines starting with # are
+    /// ignored. A pattern matches an entire word, but it may begin and/or end with a *
+    /// wildcard to match the end, start, or any part of a word (for instance, *shit*).
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    profanity_file: Option<PathBuf>,
+
+    /// Generate pronounceable pseudo-words instead of selecting words from a wordlist.
+    ///
+    /// Each pseudo-word is made of random consonant-vowel syllables, like "Tomuba". See
+    /// --syllables to control the length of each pseudo-word. The word length and
+    /// wordlist options are ignored in this mode.
+    #[structopt(long)]
+    pronounceable: bool,
+
+    /// The number of syllables in each pronounceable pseudo-word.
+    ///
+    /// Only used with --pronounceable.
+    #[structopt(long, default_value = "3", value_name = "SYLLABLES")]
+    syllables: u16,
+
+    /// Print the list of available wordlists to stdout, then exit.
+    ///
+    /// Wordlists that are aliases of other wordlists are shown as "NAME (alias of TARGET)".
     #[structopt(
         short = "L",
         long,
@@ -177,6 +905,41 @@ struct Opt {
     )]
     list_wordlists: bool,
 
+    /// Write each builtin wordlist to a file in this directory, then exit.
+    ///
+    /// Each wordlist is written to NAME.list, one word per line, so that it can be edited and
+    /// loaded again with --wordlist-file. Aliases are skipped, as are files that already
+    /// exist. The directory must already exist.
+    #[structopt(long, value_name = "DIR", parse(from_os_str))]
+    export_wordlists: Option<PathBuf>,
+
+    /// Compare two wordlists (builtin names or paths to files), then exit.
+    ///
+    /// Every word in either list is printed in alphabetical order, in a diff-like format:
+    /// words only in the first list start with "-", words only in the second list start with
+    /// "+", and words in both start with a space. Words are compared case-insensitively. The
+    /// number of words of each kind is printed at the end.
+    #[structopt(long, value_name = "WORDLIST", number_of_values = 2)]
+    compare: Vec<String>,
+
+    /// Print the source, license, and locale of a builtin wordlist to stdout, then exit
+    #[structopt(long, value_name = "WORDLIST", raw(possible_values = "WORDLIST_NAMES"))]
+    wordlist_info: Option<String>,
+
+    /// Make a builtin wordlist unavailable. May be given more than once.
+    ///
+    /// Denied wordlists are left out of --list-wordlists and --export-wordlists, and can't be
+    /// selected with --wordlist, --theme-wordlist, --compare, or $MAKEPASS_WORDLIST. Denying
+    /// a wordlist also denies its aliases. Distributions of makepass can also deny wordlists
+    /// when it's built; see wordlists/README.md.
+    #[structopt(
+        long,
+        value_name = "WORDLIST",
+        number_of_values = 1,
+        raw(possible_values = "WORDLIST_NAMES")
+    )]
+    deny_wordlist: Vec<String>,
+
     /// Print a complete wordlist to stdout, then exit
     #[structopt(short, long)]
     print_wordlist: bool,
@@ -185,24 +948,144 @@ struct Opt {
     #[structopt(short = "P", long)]
     print_filtered_wordlist: bool,
 
-    /// The number of passwords to generate when performing entropy estimations.
+    /// Print the words for --print-wordlist or --print-filtered-wordlist in this many aligned
+    /// columns, like ls.
     ///
-    /// Also the number of attempts to create a valid password (for instance, which meets the
-    /// length constraints) before giving up.
-    #[structopt(short = "S", long, default_value = "100000")]
-    sample_size: usize,
+    /// The words run down each column in turn, and every column is as wide as the longest
+    /// word. The default is one word per line.
+    #[structopt(long, value_name = "N", default_value = "1")]
+    columns: usize,
 
-    /// Use only the top TOP_WORDS words from the word list (after filtering by size).
+    /// Print how many words are left after each wordlist filter to stderr, then exit.
     ///
-    /// Using a smaller word list will make your password less secure, but possibly easier to
-    /// remember. By default, all word lists are sorted by commonality, with more common words
-    /// being near the top.
-    #[structopt(short, long, value_name = "TOP_WORDS")]
-    top_words: Option<usize>,
+    /// This shows the size of the whole wordlist, then the number of words left after the
+    /// word-length filter (see --min-word and --max-word), the profanity filter (if enabled),
+    /// and --top-words (if given), in that order. Use it to find out which setting is
+    /// unexpectedly shrinking the pool of words.
+    #[structopt(long, conflicts_with = "print_filtered_wordlist")]
+    explain_wordlist_filter: bool,
+
+    /// Print a SHA-256 digest of the filtered wordlist to stderr, or with --format, to stdout.
+    ///
+    /// The digest is computed from the words left after all of the wordlist filters, sorted,
+    /// so it doesn't depend on the order of the wordlist. If two runs print the same digest,
+    /// they chose their words from the same pool. With --format, it's printed as just the hex
+    /// digest, on its own line before the passwords (and before any CSV header), so that it
+    /// can be recorded along with them.
+    #[structopt(long)]
+    wordlist_hash: bool,
+
+    /// Reject passwords containing sequences of 3 or more characters, like "abc", "987",
+    /// or "qwe".
+    ///
+    /// Sequences are checked over the whole password, including across word boundaries,
+    /// and case-insensitively.
+    #[structopt(long)]
+    avoid_sequences: bool,
+
+    /// Reject passwords whose words have fewer than this many distinct lengths (see
+    /// --encoding).
+    ///
+    /// Words of varied lengths can make a passphrase easier to remember.
+    #[structopt(long, value_name = "N")]
+    min_distinct_word_lengths: Option<usize>,
+
+    /// Reject passwords with more than N decorations in total: each digit of the numeral, and
+    /// each symbol.
+    ///
+    /// This is a single cap on the characters that aren't part of words, for combinations of
+    /// options that add several.
+    #[structopt(long, value_name = "N")]
+    max_decorations: Option<usize>,
+
+    /// Reject passwords that don't have both a short word (at most 4 long) and a long word
+    /// (at least 7 long; see --encoding).
+    ///
+    /// A mix of short and long words can make a passphrase easier to remember. The wordlist
+    /// (after --min-word and --max-word) must have words of both kinds.
+    #[structopt(long)]
+    require_word_length_variety: bool,
+
+    /// Reject passwords that don't contain this string somewhere, like your initials.
+    ///
+    /// Unlike a fixed word, the string can be anywhere in the password, including across
+    /// words, and the check is case-sensitive. Most passwords won't contain it, so this can
+    /// reduce the entropy a lot; a warning is printed if almost none of the sampled
+    /// passwords contain the string.
+    #[structopt(long, value_name = "STR")]
+    require_substring: Option<String>,
+
+    /// Require at least one lowercase letter, uppercase letter, digit, and symbol.
+    ///
+    /// Implies --append-symbol, and requires the numeral, so it's an error to combine this
+    /// with --no-append-numeral or --no-append-symbol. Passwords that are still missing a
+    /// class (for instance, because the numeral base is above 10, so the numeral might be
+    /// a letter) are rejected.
+    #[structopt(long)]
+    require_each_class: bool,
+
+    /// Reject passwords that start with a digit, which some legacy systems don't allow.
+    #[structopt(long)]
+    no_leading_digit: bool,
+
+    /// If no password meets the length constraints, loosen them and try again.
+    ///
+    /// The maximum length is raised (or, if there isn't one, the minimum length is lowered)
+    /// a few bytes at a time, up to a few times, before giving up. Each relaxation is
+    /// reported to stderr, since the password will no longer match what was requested.
+    #[structopt(long)]
+    auto_relax: bool,
+
+    /// The number of passwords to generate when performing entropy estimations.
+    ///
+    /// The fraction of these that meet the constraints (like the length bounds) is used to
+    /// adjust the entropy estimate. If 0, no sample is taken, and the estimate isn't adjusted.
+    #[structopt(short = "S", long, default_value = "100000")]
+    sample_size: usize,
+
+    /// The number of attempts to create a valid password (for instance, which meets the
+    /// length constraints) before giving up.
+    ///
+    /// If 0, makepass keeps trying until it finds a valid password (or is interrupted with
+    /// Ctrl-C), periodically reporting its progress to stderr.
+    #[structopt(long, default_value = "10000", value_name = "N")]
+    retries: usize,
+
+    /// Use only the top TOP_WORDS words from the word list (after filtering by size).
+    ///
+    /// Using a smaller word list will make your password less secure, but possibly easier to
+    /// remember. By default, all word lists are sorted by commonality, with more common words
+    /// being near the top.
+    #[structopt(short, long, value_name = "TOP_WORDS")]
+    top_words: Option<usize>,
+
+    /// Fail unless each word contributes at least BITS of entropy.
+    ///
+    /// Each word is chosen from the wordlist after filtering (by --min-word, --max-word,
+    /// --top-words, and so on), so it contributes log2 of the number of words left; for
+    /// example, 12 bits requires at least 4096 words. This catches filters that shrink the
+    /// wordlist so much that the password would be weak.
+    #[structopt(long, value_name = "BITS")]
+    min_word_entropy: Option<f32>,
+
+    /// Fail, without printing the password, unless its estimated entropy is at least BITS.
+    ///
+    /// This checks the final estimate, after the adjustment for passwords that didn't satisfy
+    /// the constraints (see --sample-size), so it catches length bounds or filters that make
+    /// the password weaker than expected. The exit status is nonzero if the check fails.
+    #[structopt(long, value_name = "BITS")]
+    require_entropy: Option<f32>,
 
     /// Print an estimate of the password entropy to stderr.
     ///
-    /// Use --verbose to see details of how this was calculated.
+    /// Every constraint that rejects passwords (the length bounds, and filters like
+    /// --avoid-sequences or --no-leading-digit) reduces the entropy of the password. The
+    /// estimate reflects this, based on how many of a sample of passwords are rejected (see
+    /// --sample-size).
+    ///
+    /// Use --verbose to see details of how this was calculated. If stderr is a terminal, the
+    /// estimate is colored red, yellow, or green depending on its strength; set NO_COLOR to
+    /// disable this.
     #[structopt(short, long)]
     entropy_estimate: bool,
 
@@ -210,12 +1093,117 @@ struct Opt {
     #[structopt(short = "C", long)]
     show_count: bool,
 
+    /// Print the first letter of each word in the password to stderr, as a mnemonic.
+    ///
+    /// For instance, "CorrectHorseBatteryStaple" gives "CHBS". The letters are uppercase or
+    /// lowercase just as they are in the password.
+    #[structopt(long)]
+    first_letter_acronym: bool,
+
+    /// Print N other valid passwords to stderr, as alternatives to the generated one.
+    ///
+    /// This can help with choosing a password that's easy to remember. They're generated with
+    /// the same rules and constraints, so they all have the same entropy estimate, but
+    /// picking among N+1 passwords lets an attacker who knows your taste save up to log2(N+1)
+    /// bits. They're found with the attempts left over from --retries, so there may be fewer
+    /// than N.
+    #[structopt(long, value_name = "N", default_value = "0")]
+    show_alternatives: usize,
+
+    /// Seed the random number generator, so that the same SEED always produces the same
+    /// passwords.
+    ///
+    /// SEED is either a number, or 64 hex digits (as printed by --show-seed). A password
+    /// generated from a seed is only as secret as the seed, and a numeric seed is much easier
+    /// to guess than most passwords, so only use numeric seeds for testing.
+    #[structopt(long, value_name = "SEED")]
+    seed: Option<Seed>,
+
+    /// Print the seed used to generate the passwords to stderr.
+    ///
+    /// A random 256-bit seed is chosen, and can be given to --seed to reproduce exactly the
+    /// same passwords later (with the same options). Anyone who sees the seed can do the
+    /// same, so keep it as secret as the password itself; in particular, be careful about
+    /// where stderr is logged.
+    #[structopt(long)]
+    show_seed: bool,
+
+    /// Measure password generation throughput, then exit.
+    ///
+    /// Passwords are generated (and checked against the constraints) for a few seconds, then
+    /// the number of passwords generated per second, and the fraction that were rejected by
+    /// the constraints, are printed to stderr. No password is printed. If makepass was built
+    /// with the count-allocations feature, the allocations made while generating and
+    /// rendering passwords are reported too. `cargo bench` runs this with a few sets of
+    /// options.
+    #[structopt(long)]
+    benchmark: bool,
+
+    /// Print how many of SAMPLE_SIZE generated passwords satisfy the constraints to stdout,
+    /// then exit.
+    ///
+    /// This is useful for tuning the constraints, and for understanding the sampling
+    /// adjustment to the entropy estimate. No password is printed.
+    #[structopt(long)]
+    count_only: bool,
+
+    /// Estimate the entropy of an existing PASSWORD, then exit.
+    ///
+    /// The password is split into words from the wordlist (see --wordlist) and other
+    /// characters, and the entropy is estimated as if each word were chosen at random from
+    /// the wordlist, and each other character from its class (lowercase, uppercase, digit,
+    /// or symbol). This doesn't detect any other structure, so the estimate is optimistic
+    /// for passwords that weren't generated this way. Use "-" to read the password from
+    /// stdin, so that it doesn't appear in your shell history.
+    #[structopt(long, value_name = "PASSWORD")]
+    estimate_existing: Option<String>,
+
+    /// Print a JSON breakdown of the entropy estimate to stdout, then exit.
+    ///
+    /// The breakdown includes the contributions of the words, separators, numeral, and
+    /// symbol, as well as the sampling adjustment from the length constraints. The case
+    /// entropy from --random-case depends on the password, so it's always 0 here. No password
+    /// is printed.
+    #[structopt(long)]
+    explain: bool,
+
+    /// Check that the entropy estimates match the distribution of generated passwords, then
+    /// exit. This is a test for makepass itself, so it's hidden.
+    #[structopt(long, raw(hidden = "true"))]
+    self_test: bool,
+
     /// Print entropy estimate calculation details to stderr.
     ///
-    /// Implies --entropy-estimate and --show-count
+    /// This includes a histogram of the lengths of the sampled passwords. Implies
+    /// --entropy-estimate and --show-count
     #[structopt(short = "v", long)]
     verbose: bool,
 
+    /// The most detailed level of diagnostic messages to print to stderr.
+    ///
+    /// At debug, this reports progress like the size of the loaded and filtered
+    /// wordlists, and how many sample passwords were rejected. These messages
+    /// are separate from the reports printed by --verbose and --show-count.
+    #[structopt(
+        long,
+        default_value = "info",
+        value_name = "LEVEL",
+        raw(possible_values = "Level::NAMES", case_insensitive = "true")
+    )]
+    log_level: Level,
+
+    /// The assumed attacker guess rate, used to estimate crack time.
+    ///
+    /// With --verbose, the final entropy estimate is translated into the average time it
+    /// would take an attacker making this many guesses per second to crack the password.
+    #[structopt(
+        long,
+        value_name = "RATE",
+        default_value = "1e10",
+        parse(try_from_str = "parse_guess_rate")
+    )]
+    guesses_per_second: f64,
+
     /// Trailing newline behavior for the password.
     ///
     /// Whether or not to append a newline to the password. If auto, a trailing
@@ -224,13 +1212,88 @@ struct Opt {
         short = "b",
         long,
         default_value = "auto",
-        possible_value = "never",
-        possible_value = "always",
-        possible_value = "auto",
+        raw(possible_values = "NewlineBehavior::NAMES"),
         value_name = "BEHAVIOR"
     )]
     newline: NewlineBehavior,
 
+    /// Print each password using this template, instead of on its own.
+    ///
+    /// The template may contain the placeholders {password}, {words} (the words and their
+    /// separators), {numeral}, {symbol}, {checksum}, {entropy} (the estimated entropy, in
+    /// bits), and {length} (in bytes or characters, depending on --encoding). Use {{ and }}
+    /// for literal braces. With -n, the template is applied to each password.
+    #[structopt(long, value_name = "TEMPLATE")]
+    output_template: Option<Template>,
+
+    /// Print only the words of each password, separated by spaces.
+    ///
+    /// The numeral, symbols, separators, and checksum are left out of the output, for
+    /// password fields that add their own. Entropy estimates and length constraints still
+    /// apply to the whole password, so the words alone are weaker than reported.
+    #[structopt(long, conflicts_with = "output-template")]
+    show_words_only: bool,
+
+    /// Also print each password to stderr spelled out with the NATO phonetic alphabet.
+    ///
+    /// This is for reading a password aloud, like "Capital Charlie - Oscar - Romeo - Four".
+    /// Digits and symbols are spelled out by name; any other characters are printed as
+    /// themselves, in quotes.
+    #[structopt(long)]
+    phonetic: bool,
+
+    /// Print each password as a JSON object on its own line (JSON Lines).
+    ///
+    /// Each object has the password, its words, numeral, symbols, checksum, length, and
+    /// estimated entropy. Every line is flushed as soon as it's written, so that a
+    /// downstream tool can consume the passwords as they're generated.
+    #[structopt(
+        long,
+        conflicts_with = "output-template",
+        conflicts_with = "show-words-only"
+    )]
+    json_lines: bool,
+
+    /// The format to write the passwords in: "plain" (the default) or "csv".
+    ///
+    /// With "csv", there's a header row, then a row for each password, with the columns
+    /// password, entropy, length_bytes, and word_count. Passwords with commas or quotes (in
+    /// their symbols or separators) are quoted, as in RFC 4180.
+    #[structopt(
+        long,
+        value_name = "FORMAT",
+        raw(possible_values = "OutputFormat::NAMES", case_insensitive = "true"),
+        conflicts_with = "json-lines",
+        conflicts_with = "output-template",
+        conflicts_with = "show-words-only",
+        conflicts_with = "output-dir"
+    )]
+    format: Option<OutputFormat>,
+
+    /// After all the passwords are generated, write summary statistics about them as JSON to
+    /// this file, or to stderr if it's "-".
+    ///
+    /// The statistics are the number of passwords, their shortest, longest, and mean lengths
+    /// and estimated entropy, the fraction of sampled passwords that satisfied the
+    /// constraints, the size of the wordlist, and the number of distinct words used. The
+    /// passwords themselves aren't included. An existing file is overwritten.
+    #[structopt(
+        long,
+        value_name = "FILE",
+        parse(from_os_str),
+        conflicts_with = "charset"
+    )]
+    stats_json: Option<PathBuf>,
+
+    /// Read default option values from this config file.
+    ///
+    /// Defaults to $XDG_CONFIG_HOME/makepass/config.toml (or ~/.config/makepass/config.toml),
+    /// if it exists. The config file can set word_count, wordlist, newline, symbol_set, and
+    /// default_min_length, as `key = value` lines (for example, `wordlist = "english"`).
+    /// Options given on the command line take precedence over the config file.
+    #[structopt(long, value_name = "PATH", parse(from_os_str))]
+    config: Option<PathBuf>,
+
     /// Generate a shell completion file to stdout, then exit.
     #[structopt(
         short,
@@ -241,72 +1304,302 @@ struct Opt {
     gen_completions: Option<clap::Shell>,
 }
 
-// InvalidBoundsError is an error indicating that a set of bounds couldn't be
-// calculated, because the min was greater than the max
-#[derive(Debug, Clone, Error)]
-#[error("minimum length {min} greater than maximum length {max}")]
-struct InvalidBoundsError {
-    min: usize,
-    max: usize,
-}
-
 impl Opt {
-    // Get the user's requests length bounds for the whole password
-    fn length_bounds(&self) -> Result<Bounds, InvalidBoundsError> {
-        match (self.min_length, self.max_length) {
-            (None, None) => Ok(Bounds {
-                min: 24,
-                max: std::usize::MAX,
-            }),
-            (Some(min), None) => Ok(Bounds {
-                min,
-                max: std::usize::MAX,
-            }),
-            (None, Some(max)) => Ok(Bounds {
-                min: min(24, max),
-                max,
-            }),
-            (Some(min), Some(max)) if min > max => Err(InvalidBoundsError { min, max }),
-            (Some(min), Some(max)) => Ok(Bounds { min, max }),
+    /// Whether to weight the words toward the shorter ones; see --prefer-short.
+    /// Unless it's given explicitly, this is only done if the average length
+    /// of `num_words` words from `wordlist`, plus `fixed_length`, is longer
+    /// than --max-length.
+    fn should_prefer_short(
+        &self,
+        wordlist: &[&str],
+        num_words: usize,
+        fixed_length: usize,
+    ) -> bool {
+        match (self.prefer_short, self.no_prefer_short, self.max_length) {
+            (true, _, _) => true,
+            (_, true, _) | (_, _, None) => false,
+            _ if wordlist.is_empty() => false,
+            (false, false, Some(max_length)) => {
+                let total: usize = wordlist
+                    .iter()
+                    .map(|word| self.encoding.measure(word))
+                    .sum();
+                let mean = total as f64 / wordlist.len() as f64;
+
+                mean * num_words as f64 + fixed_length as f64 > max_length as f64
+            }
         }
     }
 
-    fn should_append_numeral(&self) -> bool {
-        // Explanation of logic: if append_numeral is given, it'll override
-        // no_append_numeral. If neither are given, the default is true.
-        !self.no_append_numeral
+    /// How the words in the password should be capitalized
+    fn word_case(&self) -> WordCase {
+        if let Some(style) = self.style {
+            style.resolve().1
+        } else if self.random_case {
+            WordCase::Random
+        } else if self.all_upper {
+            WordCase::Upper
+        } else if self.all_lower {
+            WordCase::Lower
+        } else {
+            WordCase::AsIs
+        }
     }
 
-    /// If a symbol should be appended, return the set of symbols to choose from.
-    fn append_symbol(&self) -> Option<&str> {
-        if self.append_symbol {
-            Some("!\"#$%&'()*+,-./\\:;<=>?@[]^_`{|}~")
-        } else if let Some(ref user_symbols) = self.symbol_set {
-            Some(user_symbols.as_str())
-        } else {
-            None
+    /// The fixed separator between words, from --separator or --style
+    fn separator(&self) -> Option<&str> {
+        match self.style {
+            Some(style) => style.resolve().0,
+            None => self.separator.as_deref(),
         }
     }
 
-    // Get the user's requested length bounds for each word
-    fn word_length_bounds(&self) -> Result<Bounds, InvalidBoundsError> {
-        match (self.min_word, self.max_word) {
-            (None, None) => Ok(Bounds { min: 4, max: 8 }),
-            (Some(min), None) => Ok(Bounds {
-                min,
-                max: max(min, 8),
-            }),
-            (None, Some(max)) => Ok(Bounds {
-                min: min(4, max),
-                max,
-            }),
-            (Some(min), Some(max)) if min > max => Err(InvalidBoundsError { min, max }),
-            (Some(min), Some(max)) => Ok(Bounds { min, max }),
+    /// The number of attempts to make at generating each password, or None if
+    /// --retries 0 asked for unlimited attempts
+    fn retry_limit(&self) -> Option<usize> {
+        match self.retries {
+            0 => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// Load the config file, and use it to fill in any options that weren't
+    /// given on the command line. It's not an error for the default config
+    /// file to be missing.
+    fn apply_config_file(&mut self, matches: &ArgMatches) -> Result<(), MakepassError> {
+        let (path, required) = match self.config {
+            Some(ref path) => (path.clone(), true),
+            None => match default_config_path() {
+                Some(path) => (path, false),
+                None => return Ok(()),
+            },
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound && !required => return Ok(()),
+            Err(source) => return Err(MakepassError::ConfigReadError { path, source }),
+        };
+
+        let config: Config = text
+            .parse()
+            .map_err(|source| MakepassError::InvalidConfig { path, source })?;
+
+        let unset = |name| matches.occurrences_of(name) == 0;
+
+        if let Some(word_count) = config.word_count.filter(|_| unset("word-count")) {
+            self.word_count = word_count;
+        }
+
+        if let Some(wordlist) = config.wordlist.filter(|_| unset("wordlist")) {
+            self.wordlist = wordlist;
+        }
+
+        if let Some(length) = config
+            .default_min_length
+            .filter(|_| unset("default-min-length"))
+        {
+            self.default_min_length = Some(length);
+        }
+
+        if let Some(newline) = config.newline.filter(|_| unset("newline")) {
+            self.newline = newline;
+        }
+
+        if unset("symbol-set") && unset("no-append-symbol") {
+            if let Some(symbol_set) = config.symbol_set {
+                self.symbol_set = Some(symbol_set);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Replace the word count, numeral, and symbol count with the ones from
+    /// --spec, if it was given. The options it replaces conflict with it, so
+    /// this only overrides the defaults and the config file.
+    fn apply_spec(&mut self) {
+        if let Some(spec) = self.spec {
+            self.word_count = spec.words;
+            self.no_append_numeral = !spec.numeral;
+
+            if spec.symbols > 0 {
+                self.symbol_count = Some(spec.symbols);
+            }
+        }
+    }
+
+    /// If the wordlist wasn't given on the command line, use the one from
+    /// $MAKEPASS_WORDLIST, if it's set. This takes precedence over the config
+    /// file.
+    fn apply_env_wordlist(&mut self, matches: &ArgMatches) -> Result<(), MakepassError> {
+        if matches.occurrences_of("wordlist") > 0 || matches.occurrences_of("wordlist-file") > 0 {
+            return Ok(());
         }
+
+        match env::var(WORDLIST_ENV_VAR) {
+            Ok(name) if !name.is_empty() => {
+                self.wordlist = name.parse().map_err(MakepassError::InvalidEnvWordlist)?;
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    /// If the wordlist wasn't given on the command line, pick the builtin
+    /// wordlist for --locale, or for $LANG if the config file didn't set a
+    /// wordlist either.
+    fn apply_locale(&mut self, matches: &ArgMatches) -> Result<(), MakepassError> {
+        if matches.occurrences_of("wordlist") > 0 || matches.occurrences_of("wordlist-file") > 0 {
+            return Ok(());
+        }
+
+        match self.locale {
+            Some(ref locale) => {
+                let name = locale_wordlist(locale)
+                    .ok_or_else(|| MakepassError::NoLocaleWordlist(locale.clone()))?;
+                self.wordlist = WordlistSelection::Named(name.to_string());
+            }
+            None if self.wordlist == WordlistSelection::Named("default".to_string()) => {
+                if let Some(name) = env::var("LANG").ok().as_deref().and_then(locale_wordlist) {
+                    self.wordlist = WordlistSelection::Named(name.to_string());
+                }
+            }
+            None => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// The options that shape the generated passwords, with their defaults
+/// filled in and checked for consistency. `run` builds the `PasswordRules`
+/// and `Constraints` from this, rather than from the `Opt` directly, so that
+/// each option is interpreted in only one place.
+#[derive(Debug, Clone)]
+struct GenerationConfig {
+    /// The bounds on the length of each word in the wordlist
+    word_length: Bounds,
+
+    /// The number of words to take from the top of the wordlist, or
+    /// usize::MAX for all of them
+    top_words: usize,
+
+    append_numeral: bool,
+
+    /// If a symbol should be appended, the set of symbols to choose from,
+    /// including any from --symbol-add. Each symbol appears only once.
+    symbol_set: Option<String>,
+
+    symbol_count: usize,
+
+    /// All of the constraints that generated passwords must satisfy
+    constraints: Constraints,
+}
+
+impl GenerationConfig {
+    // Get the user's requested length bounds for the whole password
+    fn length_bounds(opts: &Opt) -> Result<Bounds, InvalidBoundsError> {
+        if let Some(length) = opts.exact_length {
+            return Ok(Bounds {
+                min: length,
+                max: length,
+            });
+        }
+
+        // A password without words is just a few characters of numeral and
+        // symbols, so the usual minimum length would make it impossible
+        let default_min = match opts.word_count == 0 && opts.pattern.is_none() {
+            true => 0,
+            false => opts.default_min_length.unwrap_or(DEFAULT_MIN_LENGTH),
+        };
+
+        Bounds::clamp_default(opts.min_length, opts.max_length, default_min, usize::MAX)
+    }
+
+    fn symbol_set(opts: &Opt) -> Option<String> {
+        let base = if opts.append_symbol {
+            DEFAULT_SYMBOL_SET
+        } else if let Some(ref user_symbols) = opts.symbol_set {
+            user_symbols.as_str()
+        } else if opts.symbol_count.is_some()
+            || opts.symbol_position.is_some()
+            || opts.require_each_class
+            || !opts.symbol_add.is_empty()
+        {
+            DEFAULT_SYMBOL_SET
+        } else {
+            return None;
+        };
+
+        let mut symbols: Vec<&str> = Vec::new();
+
+        for symbol in split_symbols(base).chain(
+            opts.symbol_add
+                .iter()
+                .flat_map(|add| split_symbols(add.as_str())),
+        ) {
+            if !symbols.contains(&symbol) {
+                symbols.push(symbol);
+            }
+        }
+
+        Some(symbols.concat())
     }
+}
+
+impl TryFrom<&Opt> for GenerationConfig {
+    type Error = MakepassError;
+
+    fn try_from(opts: &Opt) -> Result<Self, Self::Error> {
+        let word_length = Bounds::clamp_default(opts.min_word, opts.max_word, 4, 8)
+            .map_err(MakepassError::InvalidWordLength)?;
+
+        let length = Self::length_bounds(opts).map_err(MakepassError::InvalidPasswordLength)?;
+
+        // Explanation of logic: if append_numeral is given, it'll override
+        // no_append_numeral. If neither are given, the default is true.
+        let append_numeral = !opts.no_append_numeral;
+        let symbol_set = Self::symbol_set(opts);
+        let symbol_count = opts.symbol_count.unwrap_or(1);
 
-    fn top_words(&self) -> usize {
-        self.top_words.unwrap_or(std::usize::MAX)
+        if opts.require_each_class {
+            if !append_numeral {
+                return Err(MakepassError::ClassDisabled("a numeral"));
+            }
+
+            let symbol_disabled = opts.no_append_symbol && !opts.append_symbol;
+
+            if symbol_disabled || symbol_set.is_none() || symbol_count == 0 {
+                return Err(MakepassError::ClassDisabled("a symbol"));
+            }
+        }
+
+        let filters = [
+            opts.require_substring.clone().map(Filter::Contains),
+            opts.max_decorations.map(Filter::MaxDecorations),
+            opts.avoid_sequences.then_some(Filter::NoSequences),
+            opts.min_distinct_word_lengths
+                .map(Filter::MinDistinctWordLengths),
+            opts.no_leading_digit.then_some(Filter::NoLeadingDigit),
+            opts.require_each_class.then_some(Filter::EachClass),
+            opts.require_word_length_variety
+                .then_some(Filter::WordLengthVariety),
+        ];
+
+        Ok(GenerationConfig {
+            word_length,
+            top_words: opts.top_words.unwrap_or(usize::MAX),
+            append_numeral,
+            symbol_set,
+            symbol_count,
+            constraints: Constraints {
+                length,
+                encoding: opts.encoding,
+                filters: filters.into_iter().flatten().collect(),
+            },
+        })
     }
 }
 
@@ -316,11 +1609,162 @@ enum MakepassError {
     StdoutError(#[source] io::Error),
 
     #[error("Failed to read wordlist from stdin: {0}")]
-    StdinWordlistError(#[source] io::Error),
+    StdinWordlistError(#[source] WordlistFileError),
+
+    #[error("Failed to read password from stdin: {0}")]
+    StdinPasswordError(#[source] io::Error),
 
     #[error("No such wordlist {0}")]
     NoSuchWordlist(String),
 
+    #[error(
+        "The wordlist {0} isn't available; it's been denied in this build or with --deny-wordlist"
+    )]
+    DeniedWordlist(String),
+
+    #[error("Invalid ${WORDLIST_ENV_VAR}: {0}")]
+    InvalidEnvWordlist(#[source] InvalidWordlistSelection),
+
+    #[error("No builtin wordlist for locale {0}")]
+    NoLocaleWordlist(String),
+
+    #[error(
+        "Builtin wordlist {0} can't be used with --words-from-stdin-only; use --wordlist stdin \
+         or --wordlist-file instead"
+    )]
+    BuiltinWordlistForbidden(String),
+
+    #[error("The wordlist is empty")]
+    EmptyWordlist,
+
+    #[error("The directory {} doesn't exist, or isn't a directory", .0.display())]
+    NoOutputDir(PathBuf),
+
+    #[error(
+        "Can't make password files in {} readable only by their owner on this platform; \
+         use --allow-unrestricted-files to write them anyway, with the directory's permissions",
+        .0.display()
+    )]
+    UnrestrictedOutputDir(PathBuf),
+
+    #[error("Failed to write password file {}: {source}", .path.display())]
+    PasswordFileError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Failed to write statistics to {}: {source}", .path.display())]
+    StatsError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Failed to export wordlist to {}: {source}", .path.display())]
+    ExportError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Failed to load wordlist file {}: {source}", .path.display())]
+    WordlistFileError {
+        path: PathBuf,
+        #[source]
+        source: WordlistFileError,
+    },
+
+    #[error(
+        "--require-word-length-variety needs words of length at most {} and at least {}, but \
+         the filtered wordlist doesn't have both",
+        SHORT_WORD_MAX,
+        LONG_WORD_MIN
+    )]
+    NoWordLengthVariety,
+
+    #[error("The self-test failed")]
+    SelfTestFailed,
+
+    #[error("No such category {0} in the wordlist")]
+    NoSuchCategory(String),
+
+    #[error(
+        "{theme_count} theme words were requested, but the password only has {random_words} \
+         random words"
+    )]
+    TooManyThemeWords {
+        theme_count: usize,
+        random_words: usize,
+    },
+
+    #[error("{needed} theme words are needed, but the theme wordlist only has {available}")]
+    ThemeTooSmall { needed: usize, available: usize },
+
+    #[error(
+        "Category {name} appears {needed} times in the pattern, but only has {available} \
+         usable words"
+    )]
+    CategoryTooSmall {
+        name: String,
+        needed: usize,
+        available: usize,
+    },
+
+    #[error("Failed to read config file {}: {source}", .path.display())]
+    ConfigReadError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Invalid config file {}: {source}", .path.display())]
+    InvalidConfig {
+        path: PathBuf,
+        #[source]
+        source: ConfigError,
+    },
+
+    #[error("Failed to read profanity file {}: {source}", .path.display())]
+    ProfanityFileError {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error(
+        "Fixed word position {position} is past the end of the password, which has \
+         {num_words} words"
+    )]
+    FixedWordOutOfRange { position: usize, num_words: usize },
+
+    #[error("More than one fixed word at position {0}")]
+    DuplicateFixedWord(usize),
+
+    #[error("--require-each-class requires {0} in the password, but it was disabled")]
+    ClassDisabled(&'static str),
+
+    #[error("--charset requires a length, from --char-length or --max-length")]
+    MissingCharsetLength,
+
+    #[error("The --charset password would be empty; its length must be at least 1")]
+    EmptyCharsetPassword,
+
+    #[error("Invalid password rules: {0}")]
+    InvalidRules(#[source] RulesError),
+
+    #[error(
+        "Each word only contributes {entropy:.2} bits of entropy, less than the minimum of \
+         {minimum} (see --min-word-entropy)"
+    )]
+    WeakWords { entropy: f32, minimum: f32 },
+
+    #[error(
+        "The password's estimated entropy is only {entropy:.2} bits, less than the required \
+         {required} (see --require-entropy)"
+    )]
+    InsufficientEntropy { entropy: f32, required: f32 },
+
     #[error("Invalid word length: {0}")]
     InvalidWordLength(#[source] InvalidBoundsError),
 
@@ -329,6 +1773,12 @@ enum MakepassError {
 
     #[error("Couldn't generate any passwords matching constraints, after {attempts} attempts")]
     GenFailure { attempts: usize },
+
+    #[error("--count-only and --explain need a nonzero --sample-size")]
+    NoSampleSize,
+
+    #[error("Interrupted")]
+    Interrupted,
 }
 
 fn run(opts: &Opt) -> Result<(), MakepassError> {
@@ -344,151 +1794,2050 @@ fn run(opts: &Opt) -> Result<(), MakepassError> {
 
         return WORDLIST_NAMES
             .iter()
-            .try_for_each(move |name| writeln!(stdout, "{}", name))
+            .filter(|name| !is_denied(name))
+            .try_for_each(move |name| match wordlist_alias(name) {
+                Some(target) => writeln!(stdout, "{} (alias of {})", name, target),
+                None => writeln!(stdout, "{}", name),
+            })
             .map_err(MakepassError::StdoutError);
     }
 
-    let wordlist_storage = match opts.wordlist {
-        WordlistSelection::Stdin => {
-            eprintln!("Reading wordlist from stdin...");
-            WordlistStorage::from_stream(io::stdin().lock())
+    if let Some(ref dir) = opts.export_wordlists {
+        return export_wordlists(dir);
+    }
+
+    if let [ref first, ref second] = opts.compare[..] {
+        return compare_wordlists(
+            first,
+            &load_wordlist(first, opts.word_separator.as_deref())?.as_wordlist(),
+            second,
+            &load_wordlist(second, opts.word_separator.as_deref())?.as_wordlist(),
+        );
+    }
+
+    if let Some(ref name) = opts.wordlist_info {
+        let meta = wordlist_meta(name)
+            .filter(|_| !is_denied(name))
+            .ok_or_else(|| missing_wordlist(name))?;
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        return writeln!(
+            stdout,
+            "source: {}\nlicense: {}\nlocale: {}",
+            meta.source.unwrap_or("unknown"),
+            meta.license.unwrap_or("unknown"),
+            meta.locale.unwrap_or("unknown"),
+        )
+        .map_err(MakepassError::StdoutError);
+    }
+
+    if opts.self_test {
+        return match self_test::run(&mut new_rng(opts)) {
+            true => Ok(()),
+            false => Err(MakepassError::SelfTestFailed),
+        };
+    }
+
+    if let Some(ref charset) = opts.charset {
+        let length = opts
+            .char_length
+            .or(opts.max_length)
+            .ok_or(MakepassError::MissingCharsetLength)?;
+
+        if length == 0 {
+            return Err(MakepassError::EmptyCharsetPassword);
+        }
+
+        return generate_charset_passwords(opts, charset, length);
+    }
+
+    let wordlist_storage = match (&opts.wordlist_file, &opts.wordlist) {
+        (Some(path), _) => WordlistStorage::from_file(path, opts.word_separator.as_deref())
+            .map_err(|source| MakepassError::WordlistFileError {
+                path: path.clone(),
+                source,
+            }),
+        (None, WordlistSelection::Stdin) => {
+            info!("Reading wordlist from stdin...");
+            WordlistStorage::from_stream(io::stdin().lock(), opts.word_separator.as_deref())
                 .map_err(MakepassError::StdinWordlistError)
         }
-        WordlistSelection::Named(ref name) => WordlistStorage::from_name(name)
-            .ok_or_else(|| MakepassError::NoSuchWordlist(name.clone())),
+        (None, WordlistSelection::Named(name)) if opts.words_from_stdin_only => {
+            Err(MakepassError::BuiltinWordlistForbidden(name.clone()))
+        }
+        (None, WordlistSelection::Named(name)) => {
+            WordlistStorage::from_name(name).ok_or_else(|| missing_wordlist(name))
+        }
     }?;
 
+    let wordlist_storage = match opts.compose_latin {
+        true => wordlist_storage.compose_latin(),
+        false => wordlist_storage,
+    };
+
     let wordlist = wordlist_storage.as_wordlist();
+    debug!("Loaded a wordlist of {} words", wordlist.len());
+
+    if wordlist.is_empty() && !opts.pronounceable {
+        return Err(MakepassError::EmptyWordlist);
+    }
 
     if opts.print_wordlist {
         let stdout = io::stdout();
         let mut stdout = stdout.lock();
 
-        return wordlist
-            .iter()
-            .try_for_each(move |word| writeln!(stdout, "{}", word))
+        return write_columns(&mut stdout, wordlist.as_slice(), opts.columns)
             .map_err(MakepassError::StdoutError);
     }
 
-    let word_bounds = opts
-        .word_length_bounds()
-        .map_err(MakepassError::InvalidWordLength)?;
-
-    let mut filtered_wordlist = wordlist
-        .iter()
-        .filter(move |word| word_bounds.check_len(word).is_ok())
-        .take(opts.top_words());
+    if let Some(ref password) = opts.estimate_existing {
+        let password = match password.as_str() {
+            "-" => {
+                let mut line = String::new();
+                io::stdin()
+                    .read_line(&mut line)
+                    .map_err(MakepassError::StdinPasswordError)?;
+                line.trim_end_matches(&['\r', '\n'][..]).to_string()
+            }
+            password => password.to_string(),
+        };
 
-    if opts.print_filtered_wordlist {
+        let estimate = estimate::estimate(&password, wordlist.as_slice());
+        let words: Vec<&str> = estimate.words().collect();
         let stdout = io::stdout();
         let mut stdout = stdout.lock();
 
-        return filtered_wordlist
-            .try_for_each(move |word| writeln!(stdout, "{}", word))
-            .map_err(MakepassError::StdoutError);
+        return writeln!(
+            stdout,
+            "Recognized {word_count} words from the wordlist{words}, plus {other_count} \
+             other characters.\n\
+             Estimated entropy: {entropy:.2} bits. At {rate:e} guesses per second, an attacker \
+             would need {crack_time} on average to guess this password.",
+            word_count = words.len(),
+            words = lazy_format!(if !words.is_empty() => (" ({})", words.join(", "))),
+            other_count = estimate.other_count(),
+            entropy = estimate.entropy,
+            rate = opts.guesses_per_second,
+            crack_time = display_duration(crack_time(estimate.entropy, opts.guesses_per_second)),
+        )
+        .map_err(MakepassError::StdoutError);
     }
 
-    let filtered_wordlist = Vec::from_iter(filtered_wordlist);
-    let password_rules = PasswordRules {
-        wordlist: &filtered_wordlist,
-        num_words: opts.word_count as usize,
-        append_numeral: opts.should_append_numeral(),
-        append_symbol: opts.append_symbol(),
+    let config = GenerationConfig::try_from(opts)?;
+    let word_bounds = config.word_length;
+
+    let blocklist = match opts.profanity_file {
+        Some(ref path) => fs::read_to_string(path)
+            .map(|text| Some(Blocklist::from_text(&text)))
+            .map_err(|source| MakepassError::ProfanityFileError {
+                path: path.clone(),
+                source,
+            })?,
+        None if opts.profanity_filter => Some(Blocklist::default()),
+        None => None,
     };
-    let password_bounds = opts
-        .length_bounds()
-        .map_err(MakepassError::InvalidPasswordLength)?;
 
-    let mut rng = StdRng::from_entropy();
-    let mut password_stream = password_rules
-        .stream_passwords(&mut rng)
-        .take(opts.sample_size)
-        .filter(move |password| password_bounds.check_len(password).is_ok());
+    let is_allowed_word = |word: &str| {
+        word_bounds.check_len(word, opts.encoding).is_ok()
+            && match blocklist {
+                Some(ref blocklist) => !blocklist.blocks(word),
+                None => true,
+            }
+    };
 
-    let final_password = password_stream.next().ok_or(MakepassError::GenFailure {
-        attempts: opts.sample_size,
-    })?;
+    if opts.explain_wordlist_filter {
+        let length_filtered: Vec<&str> = wordlist
+            .iter()
+            .filter(|&word| word_bounds.check_len(word, opts.encoding).is_ok())
+            .collect();
 
-    if opts.verbose || opts.entropy_estimate {
-        let success_size = 1 + password_stream.count();
+        eprintln!("{:>8} words in the wordlist", wordlist.len());
+        eprintln!(
+            "{:>8} words of {} {}",
+            length_filtered.len(),
+            word_bounds.display(),
+            opts.encoding.unit_name(),
+        );
 
-        let words_entropy = password_rules.words_entropy();
-        let numeral_entropy = password_rules.numeral_entropy();
-        let symbol_entropy = password_rules.symbol_entropy();
-        let base_entropy = words_entropy + numeral_entropy + symbol_entropy;
+        let profanity_filtered = match blocklist {
+            Some(ref blocklist) => {
+                let count = length_filtered
+                    .iter()
+                    .filter(|&&word| !blocklist.blocks(word))
+                    .count();
 
-        let entropy_adjustment = adjusted_entropy(opts.sample_size, success_size);
-        let final_entropy = base_entropy + entropy_adjustment;
+                eprintln!("{:>8} words after the profanity filter", count);
+                count
+            }
+            None => length_filtered.len(),
+        };
 
-        if opts.verbose {
+        if let Some(top_words) = opts.top_words {
             eprintln!(
-                "Generated a password of {word_count} non-repeating words, \
-                 from a set of {word_set_size} words of {word_length} bytes each: \
-                 {words_entropy:.2} bits of entropy.",
-                word_count = password_rules.num_words,
-                word_set_size = filtered_wordlist.len(),
-                word_length = word_bounds.display(),
-                words_entropy = words_entropy,
+                "{:>8} words after taking the top {}",
+                min(profanity_filtered, top_words),
+                top_words
             );
+        }
 
-            if password_rules.append_numeral {
-                eprintln!(
-                    "A random numeral in the range 0-9 was appended, for an \
-                     additional {numeral_entropy:.2} bits of entropy.",
-                    numeral_entropy = numeral_entropy,
-                );
-            }
+        return Ok(());
+    }
 
-            if let Some(special_char_set) = password_rules.append_symbol {
-                eprintln!(
-                    "A random special character from the set {special_chars} \
-                     was appended, for an additional {symbol_entropy:.2} bits of \
-                     entropy",
-                    special_chars = special_char_set,
-                    symbol_entropy = symbol_entropy
-                );
+    let mut filtered_wordlist = wordlist
+        .iter()
+        .filter(|word| is_allowed_word(word))
+        .take(config.top_words);
+
+    if opts.print_filtered_wordlist {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        // With a single column, there's no layout to compute, so the words
+        // can be written as they're filtered
+        return match opts.columns {
+            0 | 1 => filtered_wordlist.try_for_each(move |word| writeln!(stdout, "{}", word)),
+            columns => write_columns(&mut stdout, &Vec::from_iter(filtered_wordlist), columns),
+        }
+        .map_err(MakepassError::StdoutError);
+    }
+
+    let filtered_wordlist = Vec::from_iter(filtered_wordlist);
+
+    debug!(
+        "{} words remain after filtering the wordlist",
+        filtered_wordlist.len()
+    );
+
+    if opts.wordlist_hash {
+        let digest = wordlist_digest(&filtered_wordlist);
+
+        match opts.format {
+            Some(_) => writeln!(io::stdout(), "{}", digest).map_err(MakepassError::StdoutError)?,
+            None => eprintln!("Wordlist SHA-256: {}", digest),
+        }
+    }
+
+    if opts.require_word_length_variety && !opts.pronounceable {
+        let has_length = |matches: &dyn Fn(usize) -> bool| {
+            filtered_wordlist
+                .iter()
+                .any(|word| matches(opts.encoding.measure(*word)))
+        };
+
+        if !has_length(&|length| length <= SHORT_WORD_MAX)
+            || !has_length(&|length| length >= LONG_WORD_MIN)
+        {
+            return Err(MakepassError::NoWordLengthVariety);
+        }
+    }
+
+    let num_words = match opts.pattern {
+        Some(WordPattern(ref pattern)) => pattern.len(),
+        None => opts.word_count as usize,
+    };
+
+    let mut fixed_words: Vec<(usize, &str)> = Vec::with_capacity(opts.fixed_word.len());
+
+    for fixed in &opts.fixed_word {
+        if fixed.position > num_words {
+            return Err(MakepassError::FixedWordOutOfRange {
+                position: fixed.position,
+                num_words,
+            });
+        }
+
+        if fixed_words
+            .iter()
+            .any(|&(position, _)| position + 1 == fixed.position)
+        {
+            return Err(MakepassError::DuplicateFixedWord(fixed.position));
+        }
+
+        fixed_words.push((fixed.position - 1, &fixed.word));
+    }
+
+    fixed_words.sort_unstable_by_key(|&(position, _)| position);
+
+    let is_fixed = |position: usize| fixed_words.iter().any(|&(fixed, _)| fixed == position);
+
+    // If there's a pattern, filter each distinct category once. Slots that
+    // share a category share a slice, which prevents repeated words.
+    let mut categories: Vec<(&str, Vec<&str>)> = Vec::new();
+
+    // Fixed words take the place of their slots in the pattern
+    let random_slots: Vec<&String> = match opts.pattern {
+        Some(WordPattern(ref pattern)) => pattern
+            .iter()
+            .enumerate()
+            .filter(|&(position, _)| !is_fixed(position))
+            .map(|(_, name)| name)
+            .collect(),
+        None => Vec::new(),
+    };
+
+    for &name in &random_slots {
+        if categories.iter().any(|&(category, _)| category == name) {
+            continue;
+        }
+
+        let words: Vec<&str> = wordlist_storage
+            .category(name)
+            .ok_or_else(|| MakepassError::NoSuchCategory(name.clone()))?
+            .iter()
+            .map(|word| word.as_str())
+            .filter(|word| is_allowed_word(word))
+            .take(config.top_words)
+            .collect();
+
+        let needed = random_slots.iter().filter(|&&slot| slot == name).count();
+
+        if words.len() < needed {
+            return Err(MakepassError::CategoryTooSmall {
+                name: name.clone(),
+                needed,
+                available: words.len(),
+            });
+        }
+
+        categories.push((name, words));
+    }
+
+    let theme_storage = opts
+        .theme_wordlist
+        .as_deref()
+        .map(|theme| load_wordlist(theme, opts.word_separator.as_deref()))
+        .transpose()?;
+
+    let theme_wordlist = theme_storage.as_ref().map(WordlistStorage::as_wordlist);
+
+    // The theme words are taken out of the main wordlist, so that they can't
+    // repeat, and so that they don't count towards the entropy twice
+    let unthemed_wordlist: Vec<&str> = match theme_wordlist {
+        Some(ref theme) => {
+            let theme_words: HashSet<String> = theme.iter().map(str::to_lowercase).collect();
+
+            filtered_wordlist
+                .iter()
+                .copied()
+                .filter(|word| !theme_words.contains(&word.to_lowercase()))
+                .collect()
+        }
+        None => Vec::new(),
+    };
+
+    let slots: Vec<&[&str]> = match theme_wordlist {
+        Some(ref theme) => {
+            let random_words = num_words - fixed_words.len();
+            let theme_count = opts.theme_count.unwrap_or(1);
+
+            if theme_count > random_words {
+                return Err(MakepassError::TooManyThemeWords {
+                    theme_count,
+                    random_words,
+                });
+            }
+
+            if theme.len() < theme_count {
+                return Err(MakepassError::ThemeTooSmall {
+                    needed: theme_count,
+                    available: theme.len(),
+                });
             }
 
-            if success_size != opts.sample_size {
+            iter::repeat_n(theme.as_slice(), theme_count)
+                .chain(iter::repeat_n(
+                    unthemed_wordlist.as_slice(),
+                    random_words - theme_count,
+                ))
+                .collect()
+        }
+        None => random_slots
+            .iter()
+            .map(|name| {
+                categories
+                    .iter()
+                    .find(|&&(category, _)| category == name.as_str())
+                    .map(|(_, words)| words.as_slice())
+                    .expect("all categories in the pattern were loaded")
+            })
+            .collect(),
+    };
+
+    let symbol_set = config.symbol_set.as_deref();
+
+    let fixed_length = fixed_words
+        .iter()
+        .map(|&(_, word)| opts.encoding.measure(word))
+        .sum();
+
+    let word_weights = (!opts.pronounceable
+        && opts.pattern.is_none()
+        && opts.theme_wordlist.is_none()
+        && opts.exact_length.is_none()
+        && opts.should_prefer_short(
+            &filtered_wordlist,
+            num_words.saturating_sub(fixed_words.len()),
+            fixed_length,
+        ))
+    .then(|| WordWeights::prefer_short(&filtered_wordlist, opts.encoding));
+
+    if word_weights.is_some() && !opts.prefer_short {
+        info!(
+            "The words of most passwords wouldn't fit in {} {}; choosing shorter words more often \
+             (use --no-prefer-short to disable)",
+            opts.max_length.unwrap_or_default(),
+            opts.encoding.unit_name(),
+        );
+    }
+
+    let mut password_rules = PasswordRules {
+        source: match (
+            opts.pronounceable,
+            opts.pattern.is_some() || opts.theme_wordlist.is_some(),
+        ) {
+            (true, _) => WordSource::Pronounceable {
+                syllables: opts.syllables as usize,
+            },
+            (false, true) => WordSource::Categorized(&slots),
+            (false, false) => WordSource::Wordlist(&filtered_wordlist),
+        },
+        num_words,
+        separator: match (opts.separator(), &opts.separator_set) {
+            (Some(separator), _) => Separator::Fixed(separator),
+            (None, Some(set)) => Separator::Random(set),
+            (None, None) => Separator::None,
+        },
+        append_numeral: config.append_numeral,
+        numeral_base: opts.numeral_base,
+        append_symbol: symbol_set,
+        symbol_count: config.symbol_count,
+        symbol_position: opts.symbol_position.unwrap_or_default(),
+        exact_length: None,
+        fixed_words: &fixed_words,
+        append_checksum: opts.checksum,
+        sort_words: opts.sort_words,
+        fit_length: opts.fit_length,
+        word_weights: word_weights.as_ref(),
+        case: opts.word_case(),
+    };
+
+    if opts.print_spec {
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        return writeln!(stdout, "{}", PasswordSpec::from(&password_rules))
+            .map_err(MakepassError::StdoutError);
+    }
+
+    password_rules
+        .validate()
+        .map_err(MakepassError::InvalidRules)?;
+
+    if let Some(minimum) = opts.min_word_entropy {
+        let entropy = password_rules.single_word_entropy();
+
+        if entropy < minimum {
+            return Err(MakepassError::WeakWords { entropy, minimum });
+        }
+    }
+
+    if let Some(length) = opts.exact_length {
+        password_rules.exact_length = password_rules.plan_exact_length(length, opts.encoding);
+
+        if password_rules.exact_length.is_none() && opts.verbose {
+            eprintln!(
+                "Couldn't select words to make a password of exactly {length} {unit}; \
+                 falling back to discarding passwords of the wrong length.",
+                length = length,
+                unit = opts.encoding.unit_name(),
+            );
+        }
+    }
+
+    let mut constraints = config.constraints;
+    let mut rng = new_rng(opts);
+
+    if opts.benchmark {
+        interrupt::install();
+        benchmark(&password_rules, &constraints, &mut rng);
+        return Ok(());
+    }
+
+    if (opts.count_only || opts.explain) && opts.sample_size == 0 {
+        return Err(MakepassError::NoSampleSize);
+    }
+
+    if opts.count_only {
+        let success_size = sample_passwords(
+            &password_rules,
+            &constraints,
+            &mut rng,
+            Some(opts.sample_size),
+            None,
+        )
+        .count();
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        return writeln!(
+            stdout,
+            "{success_size} of {sample_size} sample passwords ({percent:.2}%) satisfied the \
+             constraints",
+            success_size = success_size,
+            sample_size = opts.sample_size,
+            percent = success_size as f64 / opts.sample_size as f64 * 100.0,
+        )
+        .map_err(MakepassError::StdoutError);
+    }
+
+    if opts.explain {
+        let success_size = sample_passwords(
+            &password_rules,
+            &constraints,
+            &mut rng,
+            Some(opts.sample_size),
+            None,
+        )
+        .count();
+
+        if success_size == 0 {
+            return Err(MakepassError::GenFailure {
+                attempts: opts.sample_size,
+            });
+        }
+
+        let report = EntropyReport::new(&password_rules, opts.sample_size, success_size);
+        let stdout = io::stdout();
+        let mut stdout = stdout.lock();
+
+        return writeln!(stdout, "{}", report.json()).map_err(MakepassError::StdoutError);
+    }
+
+    // Most passwords won't contain the substring, so check up front whether
+    // any will, rather than only finding out after searching for a long time
+    if let Some(ref fragment) = opts.require_substring {
+        if opts.sample_size > 0 {
+            let success_size = sample_passwords(
+                &password_rules,
+                &constraints,
+                &mut rng,
+                Some(opts.sample_size),
+                None,
+            )
+            .count();
+
+            if (success_size as f64) < opts.sample_size as f64 * LOW_ACCEPTANCE_RATE {
                 eprintln!(
+                    "Warning: only {} of {} sample passwords contained {:?} and satisfied the \
+                     other constraints; it may take a long time to generate passwords, and \
+                     they'll have much less entropy.",
+                    success_size, opts.sample_size, fragment,
+                );
+            }
+        }
+    }
+
+    let mut relaxations = 0;
+    let mut usage = WordUsage::new(opts.max_word_repeats, opts.unique);
+    let mut batch_report = None;
+    let mut stats = BatchStats::default();
+    let mut buffer = String::new();
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    if let Some(ref dir) = opts.output_dir {
+        if !dir.is_dir() {
+            return Err(MakepassError::NoOutputDir(dir.clone()));
+        }
+
+        if !RESTRICTS_FILE_PERMISSIONS && !opts.allow_unrestricted_files {
+            return Err(MakepassError::UnrestrictedOutputDir(dir.clone()));
+        }
+    }
+
+    // The CSV rows are written through their own handle on stdout, which is
+    // fine alongside `stdout`, since its lock is reentrant. It's flushed after
+    // the last password.
+    let mut csv = match opts.format {
+        Some(OutputFormat::Csv) => {
+            let mut csv = csv::Writer::from_writer(io::stdout());
+            csv.write_record(CSV_HEADER).map_err(csv_error)?;
+            Some(csv)
+        }
+        _ => None,
+    };
+
+    interrupt::install();
+
+    'passwords: for index in 0..opts.num_passwords {
+        if interrupt::interrupted() {
+            break;
+        }
+
+        let (final_password, report) = loop {
+            match generate_password(
+                opts,
+                &password_rules,
+                word_bounds,
+                &constraints,
+                &usage,
+                // With --fit-length, every password has its own entropy
+                index == 0 || opts.fit_length,
+                &mut rng,
+            ) {
+                Err(MakepassError::GenFailure { attempts })
+                    if opts.auto_relax && relaxations < AUTO_RELAX_LIMIT =>
+                {
+                    constraints.length = constraints
+                        .length
+                        .widen(AUTO_RELAX_STEP)
+                        .ok_or(MakepassError::GenFailure { attempts })?;
+                    relaxations += 1;
+
+                    eprintln!(
+                        "Couldn't generate a password after {attempts} attempts; relaxing the \
+                         password length to {length} {unit} and trying again.",
+                        attempts = attempts,
+                        length = constraints.length.display(),
+                        unit = opts.encoding.unit_name(),
+                    );
+                }
+                // Stop as if interrupted between passwords, so that the ones
+                // so far are still written out
+                Err(MakepassError::Interrupted) => break 'passwords,
+                result => break result?,
+            }
+        };
+
+        // Every password in a batch has the same estimate, so this only needs
+        // to check the ones that come with a report
+        if let (Some(required), Some(report)) = (opts.require_entropy, report) {
+            if report.total < required {
+                return Err(MakepassError::InsufficientEntropy {
+                    entropy: report.total,
+                    required,
+                });
+            }
+        }
+
+        usage.record(&final_password);
+
+        // The entropy estimate is only computed for the first password, but
+        // every password in a batch is generated with the same rules. With
+        // --fit-length, each password has its own report.
+        batch_report = report.or(batch_report);
+        let batch_entropy = batch_report.map(|report: EntropyReport| report.total);
+
+        if opts.stats_json.is_some() {
+            stats.record(&final_password, batch_entropy, opts.encoding);
+        }
+
+        if opts.phonetic {
+            eprintln!("{}", phonetic::spell(&final_password.to_string()));
+        }
+
+        if opts.json_lines {
+            writeln!(
+                stdout,
+                "{}",
+                password_json(&final_password, batch_entropy, opts.encoding)
+            )
+            .and_then(|()| stdout.flush())
+            .map_err(MakepassError::StdoutError)?;
+
+            continue;
+        }
+
+        if let Some(ref mut csv) = csv {
+            csv.serialize((
+                final_password.to_string(),
+                batch_entropy,
+                final_password.len(),
+                final_password.words().count(),
+            ))
+            .map_err(csv_error)?;
+
+            continue;
+        }
+
+        // Render into a reused buffer, so that each password is written with
+        // a single call, and nothing is allocated once the buffer is large
+        // enough
+        buffer.clear();
+
+        match opts.output_template {
+            Some(ref template) => write!(
+                buffer,
+                "{}",
+                template.render(&final_password, batch_entropy, opts.encoding)
+            )
+            .expect("writing to a String can't fail"),
+            None if opts.show_words_only => write!(buffer, "{}", final_password.display_words(" "))
+                .expect("writing to a String can't fail"),
+            None => final_password.write_into(&mut buffer),
+        }
+
+        if let Some(ref dir) = opts.output_dir {
+            write_password_file(dir, index, opts.num_passwords, &buffer)?;
+            continue;
+        }
+
+        stdout
+            .write_all(buffer.as_bytes())
+            .map_err(MakepassError::StdoutError)?;
+
+        if index + 1 < opts.num_passwords || opts.newline.should_print_newline() {
+            writeln!(stdout).map_err(MakepassError::StdoutError)?;
+        }
+    }
+
+    if let Some(mut csv) = csv {
+        csv.flush().map_err(MakepassError::StdoutError)?;
+    }
+
+    if let Some(ref path) = opts.stats_json {
+        let json = stats.json(batch_report.as_ref(), &password_rules.source);
+
+        let result = match path.to_str() {
+            Some("-") => writeln!(io::stderr(), "{}", json),
+            _ => fs::write(path, format!("{}\n", json)),
+        };
+
+        result.map_err(|source| MakepassError::StatsError {
+            path: path.clone(),
+            source,
+        })?;
+    }
+
+    finish_output(stdout)
+}
+
+/// Write words in aligned columns, for --columns. The words run down each
+/// column, like `ls`, and each column is padded to the width (in characters)
+/// of the longest word. There's no padding after the last column.
+fn write_columns(out: &mut impl Write, words: &[&str], columns: usize) -> io::Result<()> {
+    let width = words
+        .iter()
+        .map(|word| word.chars().count())
+        .max()
+        .unwrap_or(0);
+    let rows = words.len().div_ceil(columns.max(1));
+
+    for row in 0..rows {
+        let mut line = words.iter().skip(row).step_by(rows).peekable();
+
+        while let Some(word) = line.next() {
+            match line.peek() {
+                Some(_) => write!(out, "{:width$}  ", word, width = width)?,
+                None => writeln!(out, "{}", word)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Compute the digest for --wordlist-hash: the SHA-256 of the sorted words,
+/// each followed by a newline, as lowercase hex.
+fn wordlist_digest(words: &[&str]) -> String {
+    let mut sorted = words.to_vec();
+    sorted.sort_unstable();
+
+    let mut hasher = Sha256::new();
+
+    for word in sorted {
+        hasher.update(word.as_bytes());
+        hasher.update(b"\n");
+    }
+
+    format!("{:x}", hasher.finalize())
+}
+
+/// The error for a builtin wordlist that couldn't be loaded: either it's
+/// been denied, or it doesn't exist
+fn missing_wordlist(name: &str) -> MakepassError {
+    match is_denied(name) {
+        true => MakepassError::DeniedWordlist(name.to_string()),
+        false => MakepassError::NoSuchWordlist(name.to_string()),
+    }
+}
+
+/// Load a builtin wordlist by name or, if there isn't one with that name, a
+/// wordlist file from that path
+fn load_wordlist(
+    name_or_path: &str,
+    separator: Option<&str>,
+) -> Result<WordlistStorage, MakepassError> {
+    if is_denied(name_or_path) {
+        return Err(MakepassError::DeniedWordlist(name_or_path.to_string()));
+    }
+
+    match WordlistStorage::from_name(name_or_path) {
+        Some(storage) => Ok(storage),
+        None => WordlistStorage::from_file(name_or_path.as_ref(), separator).map_err(|source| {
+            MakepassError::WordlistFileError {
+                path: name_or_path.into(),
+                source,
+            }
+        }),
+    }
+}
+
+/// Print the differences between two wordlists, for --compare. Words are
+/// compared case-insensitively, and printed in alphabetical order (of their
+/// lowercase forms), as they're spelled in the first list that has them.
+fn compare_wordlists<'a>(
+    first_name: &str,
+    first: &Wordlist<'a>,
+    second_name: &str,
+    second: &Wordlist<'a>,
+) -> Result<(), MakepassError> {
+    let lowercase = |wordlist: &Wordlist<'a>| -> BTreeMap<String, &'a str> {
+        wordlist
+            .as_slice()
+            .iter()
+            .map(|&word| (word.to_lowercase(), word))
+            .collect()
+    };
+
+    let first_words = lowercase(first);
+    let second_words = lowercase(second);
+
+    // Where both lists have a word, the first list's spelling wins
+    let mut all_words = second_words.clone();
+    all_words.extend(first_words.iter().map(|(key, &word)| (key.clone(), word)));
+
+    let (mut only_first, mut only_second, mut both) = (0, 0, 0);
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    writeln!(stdout, "--- {} ({} words)", first_name, first_words.len())
+        .and_then(|()| writeln!(stdout, "+++ {} ({} words)", second_name, second_words.len()))
+        .map_err(MakepassError::StdoutError)?;
+
+    for (key, word) in &all_words {
+        let marker = match (
+            first_words.contains_key(key),
+            second_words.contains_key(key),
+        ) {
+            (true, true) => {
+                both += 1;
+                ' '
+            }
+            (true, false) => {
+                only_first += 1;
+                '-'
+            }
+            _ => {
+                only_second += 1;
+                '+'
+            }
+        };
+
+        writeln!(stdout, "{}{}", marker, word).map_err(MakepassError::StdoutError)?;
+    }
+
+    writeln!(
+        stdout,
+        "{only_first} only in {first_name}, {only_second} only in {second_name}, {both} in both",
+        only_first = only_first,
+        first_name = first_name,
+        only_second = only_second,
+        second_name = second_name,
+        both = both,
+    )
+    .map_err(MakepassError::StdoutError)
+}
+
+/// Write each builtin wordlist (other than aliases) to its own file in `dir`,
+/// for --export-wordlists. Existing files are left alone.
+fn export_wordlists(dir: &Path) -> Result<(), MakepassError> {
+    if !dir.is_dir() {
+        return Err(MakepassError::NoOutputDir(dir.to_path_buf()));
+    }
+
+    for &name in WORDLIST_NAMES {
+        if wordlist_alias(name).is_some() || is_denied(name) {
+            continue;
+        }
+
+        let storage = WordlistStorage::from_name(name).expect("builtin wordlists always exist");
+        let wordlist = storage.as_wordlist();
+        let path = dir.join(format!("{}.list", name));
+
+        let file = match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {
+                eprintln!("Skipping {}, which already exists", path.display());
+                continue;
+            }
+            Err(source) => return Err(MakepassError::ExportError { path, source }),
+        };
+
+        let mut file = io::BufWriter::new(file);
+
+        wordlist
+            .iter()
+            .try_for_each(|word| writeln!(file, "{}", word))
+            .and_then(|()| file.flush())
+            .map_err(|source| MakepassError::ExportError {
+                path: path.clone(),
+                source,
+            })?;
+
+        info!("Wrote {} words to {}", wordlist.len(), path.display());
+    }
+
+    Ok(())
+}
+
+/// Whether `restricted_file_options` can make files readable only by their
+/// owner on this platform
+const RESTRICTS_FILE_PERMISSIONS: bool = cfg!(unix);
+
+/// Options for creating a new file (never overwriting an existing one) to
+/// hold a password. On unix, the file is only readable by its owner. Windows
+/// has no equivalent of the file mode; setting an owner-only ACL would need
+/// the Windows API, so instead the file is hidden, and not shared with other
+/// processes while it's open. It otherwise inherits the directory's ACL.
+fn restricted_file_options() -> fs::OpenOptions {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::OpenOptionsExt;
+
+        /// FILE_ATTRIBUTE_HIDDEN, from the Windows API
+        const HIDDEN: u32 = 0x2;
+
+        options.attributes(HIDDEN).share_mode(0);
+    }
+
+    options
+}
+
+/// The fewest digits used to number the files written with --output-dir
+const OUTPUT_FILE_DIGITS: usize = 3;
+
+/// Write the password at `index` (counting from 0) to its own file in `dir`,
+/// for --output-dir. The file number is zero-padded to the width of the
+/// largest number, so that the files sort in order.
+fn write_password_file(
+    dir: &Path,
+    index: usize,
+    count: usize,
+    password: &str,
+) -> Result<(), MakepassError> {
+    let digits = max(count.to_string().len(), OUTPUT_FILE_DIGITS);
+    let path = dir.join(format!(
+        "password-{:0digits$}.txt",
+        index + 1,
+        digits = digits
+    ));
+
+    restricted_file_options()
+        .open(&path)
+        .and_then(|mut file| writeln!(file, "{}", password))
+        .map_err(|source| MakepassError::PasswordFileError { path, source })
+}
+
+/// The number of times --auto-relax will loosen the length constraints before
+/// giving up
+const AUTO_RELAX_LIMIT: usize = 5;
+
+/// The number of bytes (or characters) by which --auto-relax loosens the
+/// length constraints each time
+const AUTO_RELAX_STEP: usize = 4;
+
+/// With unlimited attempts, progress is reported every this many attempts
+const PROGRESS_INTERVAL: usize = 1_000_000;
+
+/// Make `attempts` attempts at generating a password from the rules, and
+/// yield the ones that satisfy the constraints. The number of passwords
+/// yielded, relative to `attempts`, is what's used to adjust the entropy
+/// estimate. If a histogram is given, the length of every attempt, whether or
+/// not it's accepted, is recorded in it.
+///
+/// If `attempts` is None, attempts are made until Ctrl-C is pressed, with
+/// periodic progress messages; the caller should stop once it has as many
+/// passwords as it needs. Either way, attempts stop early if Ctrl-C is
+/// pressed, so a caller that relies on all `attempts` being made must check
+/// `interrupt::interrupted` afterwards.
+fn sample_passwords<'a, 's, R: CryptoRng + Rng + ?Sized>(
+    password_rules: &'s PasswordRules<'a>,
+    constraints: &'s Constraints,
+    rng: &'s mut R,
+    attempts: Option<usize>,
+    mut histogram: Option<&'s mut LengthHistogram>,
+) -> impl Iterator<Item = Password<'a>> + 's {
+    password_rules
+        .stream_passwords_within(rng, constraints.length.max, constraints.encoding)
+        .take(attempts.unwrap_or(usize::MAX))
+        .zip(1..)
+        .take_while(|_| !interrupt::interrupted())
+        .inspect(move |&(_, count)| {
+            if attempts.is_none() && count % PROGRESS_INTERVAL == 0 {
+                info!("Tried {} passwords so far; still searching...", count);
+            }
+        })
+        .map(|(attempt, _)| attempt)
+        .inspect(move |attempt| {
+            if let Some(ref mut histogram) = histogram {
+                histogram.record(attempt.as_ref());
+            }
+        })
+        .flatten()
+        .filter(move |password| constraints.accepts(password))
+}
+
+/// The lengths of all of the sampled passwords, for --verbose. This shows
+/// whether the length constraints are rejecting more passwords than expected.
+#[derive(Debug, Clone)]
+struct LengthHistogram {
+    encoding: Encoding,
+    max_length: usize,
+    counts: BTreeMap<usize, usize>,
+
+    /// The number of passwords that were abandoned during generation, once
+    /// they were known to be longer than max_length
+    too_long: usize,
+}
+
+impl LengthHistogram {
+    /// The width of the longest bar in the histogram
+    const WIDTH: usize = 40;
+
+    fn new(constraints: &Constraints) -> Self {
+        LengthHistogram {
+            encoding: constraints.encoding,
+            max_length: constraints.length.max,
+            counts: BTreeMap::new(),
+            too_long: 0,
+        }
+    }
+
+    fn record(&mut self, attempt: Option<&Password>) {
+        match attempt {
+            Some(password) => {
+                *self
+                    .counts
+                    .entry(self.encoding.measure(password))
+                    .or_insert(0) += 1
+            }
+            None => self.too_long += 1,
+        }
+    }
+}
+
+impl Display for LengthHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let largest = self
+            .counts
+            .values()
+            .copied()
+            .chain(Some(self.too_long))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let unit = self.encoding.unit_name();
+
+        let rows: Vec<(String, usize)> = self
+            .counts
+            .iter()
+            .map(|(&length, &count)| (format!("{} {}", length, unit), count))
+            .chain(
+                (self.too_long > 0)
+                    .then(|| (format!("over {} {}", self.max_length, unit), self.too_long)),
+            )
+            .collect();
+
+        let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+
+        writeln!(f, "Lengths of the sampled passwords:")?;
+
+        rows.iter().try_for_each(|(label, count)| {
+            writeln!(
+                f,
+                "  {:>width$}: {} {}",
+                label,
+                "#".repeat((count * Self::WIDTH).div_ceil(largest)),
+                count,
+                width = label_width,
+            )
+        })
+    }
+}
+
+/// Tracks how many times each word has been used across a batch of
+/// passwords, for --max-word-repeats, and which passphrases have been used,
+/// for --unique.
+#[derive(Debug, Clone, Default)]
+struct WordUsage {
+    max_repeats: Option<usize>,
+    counts: HashMap<String, usize>,
+
+    /// The `Password::canonical_key` of each password so far, if --unique
+    /// was given
+    keys: Option<HashSet<String>>,
+}
+
+impl WordUsage {
+    fn new(max_repeats: Option<usize>, unique: bool) -> Self {
+        WordUsage {
+            max_repeats,
+            counts: HashMap::new(),
+            keys: unique.then(HashSet::new),
+        }
+    }
+
+    /// Check if a password can be added to the batch without using any word
+    /// more than the maximum number of repeats, or repeating a passphrase.
+    fn allows(&self, password: &Password) -> bool {
+        let repeats_allowed = match self.max_repeats {
+            None => true,
+            Some(max_repeats) => password
+                .words()
+                .all(|word| self.counts.get(word).copied().unwrap_or(0) <= max_repeats),
+        };
+
+        repeats_allowed
+            && self
+                .keys
+                .as_ref()
+                .is_none_or(|keys| !keys.contains(&password.canonical_key()))
+    }
+
+    fn record(&mut self, password: &Password) {
+        if self.max_repeats.is_some() {
+            for word in password.words() {
+                *self.counts.entry(word.to_string()).or_insert(0) += 1;
+            }
+        }
+
+        if let Some(ref mut keys) = self.keys {
+            keys.insert(password.canonical_key());
+        }
+    }
+}
+
+/// Generate a password from the rules, using the given source of randomness.
+/// Passwords that don't satisfy the constraints, or that would repeat words
+/// too often in the batch, are discarded. If `report` is set, print any
+/// requested entropy estimates and details to stderr.
+fn generate_password<'a, R: CryptoRng + Rng + ?Sized>(
+    opts: &Opt,
+    password_rules: &PasswordRules<'a>,
+    word_bounds: Bounds,
+    constraints: &Constraints,
+    usage: &WordUsage,
+    report: bool,
+    rng: &mut R,
+) -> Result<(Password<'a>, Option<EntropyReport>), MakepassError> {
+    let mut alternatives: Vec<Password> = Vec::with_capacity(opts.show_alternatives);
+
+    let final_password = {
+        // With unlimited attempts, the stream only ends early if it's
+        // interrupted; otherwise, it also ends when the attempts run out
+        let mut candidates =
+            sample_passwords(password_rules, constraints, rng, opts.retry_limit(), None)
+                .filter(|password| usage.allows(password));
+
+        let final_password = candidates.next().ok_or_else(|| {
+            match (opts.retry_limit(), interrupt::interrupted()) {
+                (Some(attempts), false) => MakepassError::GenFailure { attempts },
+                _ => MakepassError::Interrupted,
+            }
+        })?;
+
+        // The alternatives come from the attempts that are left after finding
+        // the password, so that they never need more than --retries in total.
+        // If those run out, fewer alternatives are shown.
+        if opts.show_alternatives > 0 {
+            for candidate in candidates {
+                if candidate != final_password && !alternatives.contains(&candidate) {
+                    alternatives.push(candidate);
+
+                    if alternatives.len() == opts.show_alternatives {
+                        break;
+                    }
+                }
+            }
+        }
+
+        final_password
+    };
+
+    // With --fit-length, the rules only describe this password once they have
+    // its actual number of words
+    let fitted_rules;
+    let password_rules = if password_rules.fit_length {
+        fitted_rules = password_rules.with_num_words(final_password.words().count());
+        &fitted_rules
+    } else {
+        password_rules
+    };
+
+    let wants_entropy = opts.json_lines
+        || opts.format == Some(OutputFormat::Csv)
+        || opts
+            .output_template
+            .as_ref()
+            .is_some_and(|template| template.uses(Placeholder::Entropy));
+
+    let mut entropy_report = None;
+
+    if report
+        && (opts.verbose
+            || opts.entropy_estimate
+            || wants_entropy
+            || opts.stats_json.is_some()
+            || opts.require_entropy.is_some()
+            || logging::enabled(Level::Debug))
+    {
+        // A fitted password always fits the maximum length, by construction,
+        // so with --fit-length its entropy comes directly from its number of
+        // words, without sampling for every password
+        let sample_size = match password_rules.fit_length {
+            true => 0,
+            false => opts.sample_size,
+        };
+
+        // Collecting the histogram has some overhead, so only do it when
+        // it'll be printed
+        let mut histogram =
+            (opts.verbose && sample_size > 0).then(|| LengthHistogram::new(constraints));
+
+        // The entropy estimate is adjusted by the fraction of a separate
+        // sample of passwords that satisfy the constraints
+        let report = match sample_size {
+            0 => EntropyReport::unadjusted(password_rules),
+            sample_size => {
+                let success_size = sample_passwords(
+                    password_rules,
+                    constraints,
+                    rng,
+                    Some(sample_size),
+                    histogram.as_mut(),
+                )
+                .filter(|password| usage.allows(password))
+                .count();
+
+                // A partial sample would skew the estimate
+                if interrupt::interrupted() {
+                    return Err(MakepassError::Interrupted);
+                }
+
+                debug!(
+                    "Generated {} sample passwords; {} were rejected",
+                    sample_size,
+                    sample_size - success_size
+                );
+
+                // A password was found, so the constraints can be satisfied,
+                // even if none of the sample did
+                EntropyReport::new(password_rules, sample_size, success_size.max(1))
+            }
+        }
+        .with_case(password_rules.case_entropy(&final_password));
+
+        // The report adds up the same components as password_entropy (which
+        // also checks that the password matches its rules), plus the
+        // sampling adjustment
+        debug_assert!(
+            (report.total - report.adjustment - password_rules.password_entropy(&final_password))
+                .abs()
+                < 1e-3,
+            "entropy report {:?} doesn't match the rules",
+            report,
+        );
+
+        if opts.verbose {
+            match password_rules.source {
+                WordSource::Wordlist(wordlist) => {
+                    eprintln_wrapped!(
+                        "Generated a password of {word_count} non-repeating words, \
+                         from a set of {word_set_size} words of {word_length} {unit} each: \
+                         {words_entropy:.2} bits of entropy.",
+                        word_count = password_rules.num_words,
+                        word_set_size = wordlist.len(),
+                        word_length = word_bounds.display(),
+                        unit = opts.encoding.unit_name(),
+                        words_entropy = report.words,
+                    );
+
+                    if let (Some(shortest), Some(longest)) = (
+                        wordlist
+                            .iter()
+                            .map(|word| opts.encoding.measure(word))
+                            .min(),
+                        wordlist
+                            .iter()
+                            .map(|word| opts.encoding.measure(word))
+                            .max(),
+                    ) {
+                        let total: usize = wordlist
+                            .iter()
+                            .map(|word| opts.encoding.measure(word))
+                            .sum();
+
+                        eprintln_wrapped!(
+                            "The words in that set are between {shortest} and {longest} {unit} \
+                             long, with an average length of {mean:.2} {unit}.",
+                            shortest = shortest,
+                            longest = longest,
+                            unit = opts.encoding.unit_name(),
+                            mean = total as f32 / wordlist.len() as f32,
+                        );
+                    }
+
+                    if password_rules.exact_length.is_some() {
+                        eprintln_wrapped!(
+                            "Only combinations of words that bring the password to a length of \
+                             exactly {length} {unit} were considered.",
+                            length = constraints.length.min,
+                            unit = opts.encoding.unit_name(),
+                        );
+                    }
+                }
+                WordSource::Categorized(slots) if opts.theme_wordlist.is_some() => {
+                    eprintln_wrapped!(
+                        "Generated a password of {word_count} non-repeating words, \
+                         {theme_count} from a theme wordlist of {theme_size} words and the rest \
+                         from a set of {word_set_size} words of {word_length} {unit} each: \
+                         {words_entropy:.2} bits of entropy.",
+                        word_count = password_rules.num_words,
+                        theme_count = opts.theme_count.unwrap_or(1),
+                        theme_size = slots.first().map_or(0, |slot| slot.len()),
+                        word_set_size = slots
+                            .get(opts.theme_count.unwrap_or(1))
+                            .map_or(0, |slot| slot.len()),
+                        word_length = word_bounds.display(),
+                        unit = opts.encoding.unit_name(),
+                        words_entropy = report.words,
+                    )
+                }
+                WordSource::Categorized(slots) => eprintln_wrapped!(
+                    "Generated a password of {word_count} words following the pattern \
+                     {pattern}, from categories of {sizes} words of {word_length} {unit} \
+                     each: {words_entropy:.2} bits of entropy.",
+                    word_count = password_rules.num_words,
+                    pattern = opts
+                        .pattern
+                        .as_ref()
+                        .map(|WordPattern(pattern)| pattern.join(","))
+                        .unwrap_or_default(),
+                    sizes = slots
+                        .iter()
+                        .map(|slot| slot.len().to_string())
+                        .collect::<Vec<_>>()
+                        .join("/"),
+                    word_length = word_bounds.display(),
+                    unit = opts.encoding.unit_name(),
+                    words_entropy = report.words,
+                ),
+                WordSource::Pronounceable { syllables } => eprintln_wrapped!(
+                    "Generated a password of {word_count} pronounceable words of \
+                     {syllables} syllables each, from a set of {syllable_count} possible \
+                     syllables: {words_entropy:.2} bits of entropy.",
+                    word_count = password_rules.num_words,
+                    syllables = syllables,
+                    syllable_count = pronounceable::syllable_count(),
+                    words_entropy = report.words,
+                ),
+            }
+
+            if !password_rules.fixed_words.is_empty() {
+                eprintln_wrapped!(
+                    "The words at positions {positions} were fixed, so they add no entropy.",
+                    positions = password_rules
+                        .fixed_words
+                        .iter()
+                        .map(|&(position, _)| (position + 1).to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                );
+            }
+
+            if password_rules.sort_words {
+                eprintln_wrapped!(
+                    "The words were sorted alphabetically, which cost {order_entropy:.2} bits of \
+                     entropy; this is included above.",
+                    order_entropy = password_rules.order_entropy(),
+                );
+            }
+
+            if let Separator::Random(set) = password_rules.separator {
+                eprintln_wrapped!(
+                    "A random separator from the set {set} was placed between each pair of \
+                     words, for an additional {separator_entropy:.2} bits of entropy.",
+                    set = set,
+                    separator_entropy = report.separators,
+                );
+            }
+
+            if password_rules.append_numeral {
+                eprintln_wrapped!(
+                    "A random base {base} numeral in the range 0-{max_digit} was appended, \
+                     for an additional {numeral_entropy:.2} bits of entropy.",
+                    base = password_rules.numeral_base,
+                    max_digit = char::from_digit(password_rules.numeral_base as u32 - 1, 36)
+                        .expect("numeral base is at most 36"),
+                    numeral_entropy = report.numeral,
+                );
+            }
+
+            if let Some(special_char_set) = password_rules.append_symbol {
+                eprintln_wrapped!(
+                    "{count} from the set {special_chars} {was} {placed}, for an \
+                     additional {symbol_entropy:.2} bits of entropy",
+                    count = lazy_format!(match (password_rules.symbol_count) {
+                        1 => "A random special character",
+                        count => ("{} random special characters", count),
+                    }),
+                    special_chars = special_char_set,
+                    was = match password_rules.symbol_count {
+                        1 => "was",
+                        _ => "were",
+                    },
+                    placed = match password_rules.symbol_position {
+                        SymbolPosition::Start => "prepended",
+                        SymbolPosition::End => "appended",
+                        SymbolPosition::Both => "placed at each end of the password",
+                    },
+                    symbol_entropy = report.symbol,
+                );
+            }
+
+            if password_rules.case == WordCase::Random {
+                eprintln_wrapped!(
+                    "The case of each of the {letters} letters in the words was randomized, for \
+                     an additional {case_entropy:.2} bits of entropy.",
+                    letters = report.case as usize,
+                    case_entropy = report.case,
+                );
+            }
+
+            if password_rules.append_checksum {
+                eprintln_wrapped!(
+                    "A checksum character was appended; it's computed from the rest of the \
+                     password, so it adds no entropy."
+                );
+            }
+
+            if opts.sample_size == 0 {
+                eprintln_wrapped!(
+                    "No passwords were sampled (--sample-size 0), so the entropy estimate isn't \
+                     adjusted for passwords that didn't have {constraints}.",
+                    constraints = constraints,
+                );
+            } else if report.success_size != report.sample_size {
+                eprintln_wrapped!(
                     "{sample_size} sample passwords were generated, but only {success_size} \
-                     had a length of {password_length} bytes. The entropy estimate was adjusted \
-                     accordingly by {adjust_entropy:.2} bits.",
-                    sample_size = opts.sample_size,
-                    success_size = success_size,
-                    password_length = password_bounds.display(),
-                    adjust_entropy = entropy_adjustment,
+                     had {constraints}. The entropy estimate was adjusted accordingly by \
+                     {adjust_entropy:.2} bits.",
+                    sample_size = report.sample_size,
+                    success_size = report.success_size,
+                    constraints = constraints,
+                    adjust_entropy = report.adjustment,
                 );
             }
+
+            if let Some(ref histogram) = histogram {
+                eprint!("{}", histogram);
+            }
         }
 
+        entropy_report = Some(report);
+
+        if opts.verbose || opts.entropy_estimate {
+            eprintln!(
+                "Estimated total password entropy: {entropy} bits.",
+                entropy = display_entropy(report.total, should_color_stderr()),
+            );
+        }
+
+        if opts.verbose {
+            eprintln_wrapped!(
+                "At {rate:e} guesses per second, an attacker would need \
+                 {crack_time} on average to guess this password.",
+                rate = opts.guesses_per_second,
+                crack_time = display_duration(crack_time(report.total, opts.guesses_per_second)),
+            );
+        }
+    }
+
+    if opts.verbose || opts.show_count {
         eprintln!(
-            "Estimated total password entropy: {entropy:.2} bits.",
-            entropy = final_entropy
+            "The password is {} bytes ({} characters)",
+            final_password.len(),
+            final_password.char_len()
         );
     }
 
-    if opts.verbose || opts.show_count {
-        eprintln!("The password is {} bytes", final_password.len());
+    if opts.first_letter_acronym {
+        eprintln!("Acronym: {}", final_password.acronym());
     }
 
-    print!("{}", final_password);
+    if opts.show_alternatives > 0 {
+        eprintln!(
+            "Alternatives (equally strong; use at most one, in place of the generated password):"
+        );
+
+        for alternative in &alternatives {
+            eprintln!("  {}", alternative);
+        }
 
-    if opts.newline.should_print_newline() {
-        println!();
+        if alternatives.len() < opts.show_alternatives {
+            eprintln!(
+                "  (only found {} of {} alternatives within --retries)",
+                alternatives.len(),
+                opts.show_alternatives,
+            );
+        }
     }
 
-    Ok(())
+    Ok((final_password, entropy_report))
 }
 
+/// Generate and print passwords for --charset, which don't use any of the
+/// wordlist machinery.
+fn generate_charset_passwords(
+    opts: &Opt,
+    charset: &Charset,
+    length: usize,
+) -> Result<(), MakepassError> {
+    if opts.verbose || opts.entropy_estimate {
+        let entropy = charset.entropy(length);
+
+        if opts.verbose {
+            eprintln!(
+                "Generated a password of {length} characters, each from a set of {charset_size} \
+                 characters: {entropy:.2} bits of entropy.",
+                length = length,
+                charset_size = charset.0.len(),
+                entropy = entropy,
+            );
+        }
+
+        eprintln!(
+            "Estimated total password entropy: {entropy} bits.",
+            entropy = display_entropy(entropy, should_color_stderr()),
+        );
+    }
+
+    let mut rng = new_rng(opts);
+    let stdout = io::stdout();
+    let mut stdout = stdout.lock();
+
+    interrupt::install();
+
+    for index in 0..opts.num_passwords {
+        if interrupt::interrupted() {
+            break;
+        }
+
+        write!(stdout, "{}", charset.gen_string(&mut rng, length))
+            .map_err(MakepassError::StdoutError)?;
+
+        if index + 1 < opts.num_passwords || opts.newline.should_print_newline() {
+            writeln!(stdout).map_err(MakepassError::StdoutError)?;
+        }
+    }
+
+    finish_output(stdout)
+}
+
+/// Flush the passwords written so far, then report whether generation was cut
+/// short by Ctrl-C.
+/// Writing a CSV row can only fail by failing to write to stdout
+fn csv_error(err: csv::Error) -> MakepassError {
+    MakepassError::StdoutError(err.into())
+}
+
+fn finish_output(mut stdout: impl Write) -> Result<(), MakepassError> {
+    stdout.flush().map_err(MakepassError::StdoutError)?;
+
+    match interrupt::interrupted() {
+        true => Err(MakepassError::Interrupted),
+        false => Ok(()),
+    }
+}
+
+/// How long to generate passwords for with --benchmark
+const BENCHMARK_DURATION: Duration = Duration::from_secs(2);
+
+/// Generate passwords for BENCHMARK_DURATION, and report the throughput and
+/// rejection rate to stderr.
+fn benchmark<R: CryptoRng + Rng + ?Sized>(
+    password_rules: &PasswordRules,
+    constraints: &Constraints,
+    rng: &mut R,
+) {
+    // Check the clock only every so often, so that it doesn't dominate the
+    // measurement
+    const BATCH_SIZE: usize = 1000;
+
+    let start = Instant::now();
+    let mut generated: usize = 0;
+    let mut accepted: usize = 0;
+
+    // If interrupted, report on what was generated so far
+    while start.elapsed() < BENCHMARK_DURATION && !interrupt::interrupted() {
+        accepted +=
+            sample_passwords(password_rules, constraints, rng, Some(BATCH_SIZE), None).count();
+        generated += BATCH_SIZE;
+    }
+
+    let elapsed = start.elapsed().as_secs_f64();
+    let rejected = generated - accepted;
+
+    eprintln!(
+        "Generated {generated} passwords in {elapsed:.2} seconds ({rate:.0} passwords per \
+         second, {accepted_rate:.0} acceptable passwords per second).",
+        generated = generated,
+        elapsed = elapsed,
+        rate = generated as f64 / elapsed,
+        accepted_rate = accepted as f64 / elapsed,
+    );
+
+    eprintln!(
+        "{rejected} passwords ({percent:.2}%) were rejected by the constraints.",
+        rejected = rejected,
+        percent = rejected as f64 / generated as f64 * 100.0,
+    );
+
+    #[cfg(feature = "count-allocations")]
+    compare_generate_allocations(password_rules, constraints, rng);
+
+    #[cfg(feature = "count-allocations")]
+    compare_render_allocations(password_rules, rng);
+}
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: alloc_count::CountingAllocator = alloc_count::CountingAllocator;
+
+/// For --benchmark, with the count-allocations feature and a maximum length:
+/// report how many allocations (and how long) it takes to make a batch of
+/// attempts with `gen_password`, checking the length afterwards, compared to
+/// `gen_password_within`, which abandons over-long passwords early.
+#[cfg(feature = "count-allocations")]
+fn compare_generate_allocations<R: CryptoRng + Rng + ?Sized>(
+    password_rules: &PasswordRules,
+    constraints: &Constraints,
+    rng: &mut R,
+) {
+    const ATTEMPT_COUNT: usize = 100_000;
+
+    let max_length = constraints.length.max;
+    let encoding = constraints.encoding;
+
+    if max_length == usize::MAX {
+        return;
+    }
+
+    let start = (alloc_count::allocations(), Instant::now());
+    for _ in 0..ATTEMPT_COUNT {
+        let password = password_rules.gen_password(rng);
+        std::hint::black_box(encoding.measure(&password) <= max_length);
+    }
+    let complete = (alloc_count::allocations() - start.0, start.1.elapsed());
+
+    let start = (alloc_count::allocations(), Instant::now());
+    for _ in 0..ATTEMPT_COUNT {
+        std::hint::black_box(password_rules.gen_password_within(rng, max_length, encoding));
+    }
+    let early = (alloc_count::allocations() - start.0, start.1.elapsed());
+
+    eprintln!(
+        "Making {count} attempts took {complete} allocations ({complete_time:.1} ms) when \
+         checking complete passwords against the maximum length, and {early} ({early_time:.1} ms) \
+         when abandoning them early.",
+        count = ATTEMPT_COUNT,
+        complete = complete.0,
+        complete_time = complete.1.as_secs_f64() * 1000.0,
+        early = early.0,
+        early_time = early.1.as_secs_f64() * 1000.0,
+    );
+}
+
+/// For --benchmark, with the count-allocations feature: report how many
+/// allocations it takes to render a batch of passwords with `to_string`,
+/// compared to `Password::write_into` with a reused buffer.
+#[cfg(feature = "count-allocations")]
+fn compare_render_allocations<R: CryptoRng + Rng + ?Sized>(
+    password_rules: &PasswordRules,
+    rng: &mut R,
+) {
+    const RENDER_COUNT: usize = 10_000;
+
+    let passwords: Vec<Password> = (0..RENDER_COUNT)
+        .map(|_| password_rules.gen_password(rng))
+        .collect();
+
+    let start = alloc_count::allocations();
+    for password in &passwords {
+        std::hint::black_box(password.to_string());
+    }
+    let with_to_string = alloc_count::allocations() - start;
+
+    let start = alloc_count::allocations();
+    let mut buffer = String::new();
+    for password in &passwords {
+        buffer.clear();
+        password.write_into(&mut buffer);
+        std::hint::black_box(&buffer);
+    }
+    let with_buffer = alloc_count::allocations() - start;
+
+    eprintln!(
+        "Rendering {count} passwords took {with_to_string} allocations with to_string, and \
+         {with_buffer} with a reused buffer.",
+        count = RENDER_COUNT,
+        with_to_string = with_to_string,
+        with_buffer = with_buffer,
+    );
+}
+
+/// The random number generator used to generate passwords. This is rand's
+/// StdRng, unless the chacha20 feature is enabled.
+#[cfg(not(feature = "chacha20"))]
+type PasswordRng = rand::rngs::StdRng;
+
+#[cfg(feature = "chacha20")]
+type PasswordRng = rand_chacha::ChaCha20Rng;
+
+/// The name of PasswordRng, for --verbose
+#[cfg(not(feature = "chacha20"))]
+const RNG_NAME: &str = "StdRng (from the rand crate)";
+
+#[cfg(feature = "chacha20")]
+const RNG_NAME: &str = "ChaCha20";
+
+/// Create the source of randomness used to generate passwords. This is
+/// seeded from the OS, unless a seed is given (see --seed). With
+/// --show-seed, the seed is printed to stderr; a random seed is generated
+/// explicitly so that it can be.
+fn new_rng(opts: &Opt) -> PasswordRng {
+    if opts.verbose {
+        eprintln!("Using the {} random number generator.", RNG_NAME);
+    }
+
+    let seed = match opts.seed {
+        Some(ref seed) => seed.clone(),
+        None if opts.show_seed => {
+            let mut bytes = [0; 32];
+            OsRng.fill(&mut bytes);
+            Seed::Full(bytes)
+        }
+        None => return PasswordRng::from_entropy(),
+    };
+
+    if opts.show_seed {
+        eprintln!("Seed: {}", seed);
+    }
+
+    match seed {
+        Seed::Number(seed) => PasswordRng::seed_from_u64(seed),
+        Seed::Full(bytes) => PasswordRng::from_seed(bytes),
+    }
+}
+
+/// With --require-substring, warn if fewer than this fraction of the sample
+/// passwords are accepted
+const LOW_ACCEPTANCE_RATE: f64 = 0.001;
+
 fn adjusted_entropy(sample_size: usize, success_size: usize) -> f32 {
     (success_size as f32).log2() - (sample_size as f32).log2()
 }
 
+/// A breakdown of the estimated entropy of a generated password, by source
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct EntropyReport {
+    words: f32,
+    separators: f32,
+    numeral: f32,
+    symbol: f32,
+
+    /// The entropy from --random-case. This depends on the password, so it's
+    /// 0 unless it's added with `with_case`.
+    case: f32,
+    sample_size: usize,
+    success_size: usize,
+    adjustment: f32,
+    total: f32,
+}
+
+impl EntropyReport {
+    fn new(rules: &PasswordRules, sample_size: usize, success_size: usize) -> Self {
+        let words = rules.words_entropy();
+        let separators = rules.separator_entropy();
+        let numeral = rules.numeral_entropy();
+        let symbol = rules.symbol_entropy();
+        let adjustment = adjusted_entropy(sample_size, success_size);
+
+        EntropyReport {
+            words,
+            separators,
+            numeral,
+            symbol,
+            case: 0.0,
+            sample_size,
+            success_size,
+            adjustment,
+            total: words + separators + numeral + symbol + adjustment,
+        }
+    }
+
+    /// A report with no sampling adjustment, for when no passwords were
+    /// sampled. The sample and success sizes are both 0.
+    fn unadjusted(rules: &PasswordRules) -> Self {
+        EntropyReport {
+            sample_size: 0,
+            success_size: 0,
+            adjustment: 0.0,
+            ..EntropyReport::new(rules, 1, 1)
+        }
+    }
+
+    /// Add the entropy from randomizing the case of a particular password's
+    /// letters; see `PasswordRules::case_entropy`.
+    fn with_case(self, case: f32) -> Self {
+        EntropyReport {
+            case,
+            total: self.total - self.case + case,
+            ..self
+        }
+    }
+
+    /// Write this report as a single-line JSON object
+    fn json(&self) -> impl Display + '_ {
+        lazy_format!(
+            "{{\"words\":{},\"separators\":{},\"numeral\":{},\"symbol\":{},\"case\":{},\
+             \"sample_size\":{},\"success_size\":{},\"sampling_adjustment\":{},\
+             \"total\":{}}}",
+            self.words,
+            self.separators,
+            self.numeral,
+            self.symbol,
+            self.case,
+            self.sample_size,
+            self.success_size,
+            self.adjustment,
+            self.total,
+        )
+    }
+}
+
+/// Aggregate statistics about a batch of generated passwords, for
+/// --stats-json. Only the lengths, entropy, and words are kept, not the
+/// passwords themselves.
+#[derive(Debug, Clone, Default)]
+struct BatchStats {
+    count: usize,
+    min_length: usize,
+    max_length: usize,
+    total_length: usize,
+
+    /// The lowest, highest, and total entropy, if it was estimated
+    entropy: Option<(f32, f32, f32)>,
+
+    /// The distinct words used across all of the passwords
+    words: HashSet<String>,
+}
+
+impl BatchStats {
+    fn record(&mut self, password: &Password, entropy: Option<f32>, encoding: Encoding) {
+        let length = encoding.measure(password);
+
+        self.min_length = match self.count {
+            0 => length,
+            _ => min(self.min_length, length),
+        };
+        self.max_length = max(self.max_length, length);
+        self.total_length += length;
+        self.count += 1;
+
+        if let Some(entropy) = entropy {
+            self.entropy = Some(match self.entropy {
+                None => (entropy, entropy, entropy),
+                Some((lowest, highest, total)) => {
+                    (lowest.min(entropy), highest.max(entropy), total + entropy)
+                }
+            });
+        }
+
+        for word in password.words() {
+            if !self.words.contains(word) {
+                self.words.insert(word.to_string());
+            }
+        }
+    }
+
+    /// Write these statistics as a single-line JSON object. The acceptance
+    /// rate comes from the entropy report's sample, so it's null if no
+    /// passwords were sampled; the wordlist size is null for pronounceable
+    /// words.
+    fn json<'a>(
+        &'a self,
+        report: Option<&'a EntropyReport>,
+        source: &WordSource,
+    ) -> impl Display + 'a {
+        let wordlist_size = match *source {
+            WordSource::Wordlist(wordlist) => Some(wordlist.len()),
+            WordSource::Categorized(slots) => Some(
+                slots
+                    .iter()
+                    .flat_map(|slot| slot.iter())
+                    .collect::<HashSet<_>>()
+                    .len(),
+            ),
+            WordSource::Pronounceable { .. } => None,
+        };
+
+        lazy_format!(
+            "{{\"passwords\":{},\"length\":{},\"entropy\":{},\"acceptance_rate\":{},\
+             \"wordlist_size\":{},\"distinct_words\":{}}}",
+            self.count,
+            lazy_format!(match (self.count) {
+                0 => "null",
+                _ => (
+                    "{{\"min\":{},\"max\":{},\"mean\":{}}}",
+                    self.min_length,
+                    self.max_length,
+                    self.total_length as f64 / self.count as f64,
+                ),
+            }),
+            lazy_format!(match (self.entropy) {
+                None => "null",
+                Some((lowest, highest, total)) => (
+                    "{{\"min\":{},\"max\":{},\"mean\":{}}}",
+                    lowest,
+                    highest,
+                    total / self.count as f32,
+                ),
+            }),
+            lazy_format!(match (report) {
+                Some(report) if report.sample_size > 0 =>
+                    ("{}", report.success_size as f64 / report.sample_size as f64),
+                _ => "null",
+            }),
+            lazy_format!(match (wordlist_size) {
+                Some(size) => ("{}", size),
+                None => "null",
+            }),
+            self.words.len(),
+        )
+    }
+}
+
+/// A password as a single-line JSON object, for --json-lines. The numeral,
+/// symbols, and checksum are null if the password doesn't have them, as is
+/// the entropy if it wasn't estimated.
+#[derive(Debug, Serialize)]
+struct PasswordJson<'a> {
+    password: String,
+    words: Vec<&'a str>,
+    numeral: Option<String>,
+    symbols: Option<String>,
+    checksum: Option<String>,
+    length: usize,
+    entropy: Option<f32>,
+}
+
+fn password_json(password: &Password, entropy: Option<f32>, encoding: Encoding) -> String {
+    let matching = |filter: fn(&Component) -> bool| {
+        let text: String = password
+            .components()
+            .filter(filter)
+            .map(|component| component.to_string())
+            .collect();
+
+        Some(text).filter(|text| !text.is_empty())
+    };
+
+    serde_json::to_string(&PasswordJson {
+        password: password.to_string(),
+        words: password.words().collect(),
+        numeral: matching(|component| matches!(component, Component::Numeral(_))),
+        symbols: matching(|component| matches!(component, Component::Symbol(_))),
+        checksum: matching(|component| matches!(component, Component::Checksum(_))),
+        length: encoding.measure(password),
+        entropy,
+    })
+    .expect("a password can always be serialized")
+}
+
+/// Entropy estimates below this many bits are shown in red, to make weak
+/// configurations obvious
+const WEAK_ENTROPY: f32 = 40.0;
+
+/// Entropy estimates above this many bits are shown in green; anything in
+/// between is yellow
+const STRONG_ENTROPY: f32 = 70.0;
+
+/// Check if output to stderr should be colored: it must be a tty, and the
+/// user mustn't have opted out with NO_COLOR (see https://no-color.org).
+fn should_color_stderr() -> bool {
+    env::var_os("NO_COLOR").is_none_or(|value| value.is_empty()) && atty::is(atty::Stream::Stderr)
+}
+
+/// Display an entropy estimate, optionally colored with ANSI escapes
+/// according to how strong it is.
+fn display_entropy(entropy: f32, color: bool) -> impl Display {
+    let code = match entropy {
+        entropy if entropy < WEAK_ENTROPY => "31",
+        entropy if entropy <= STRONG_ENTROPY => "33",
+        _ => "32",
+    };
+
+    lazy_format!(
+        if color => ("\x1b[{}m{:.2}\x1b[0m", code, entropy)
+        else => ("{:.2}", entropy)
+    )
+}
+
+/// The average number of seconds it would take to guess a password with the
+/// given entropy. On average an attacker has to search half the space.
+fn crack_time(entropy: f32, guesses_per_second: f64) -> f64 {
+    (entropy as f64 - 1.0).exp2() / guesses_per_second
+}
+
 fn main() {
-    let opts = Opt::from_args();
-    if let Err(err) = run(&opts) {
-        eprintln!("{}", err);
-        exit(1);
+    let matches = Opt::clap().get_matches();
+    let mut opts = Opt::from_clap(&matches);
+    logging::set_level(opts.log_level);
+    wordlists::deny_wordlists(opts.deny_wordlist.clone());
+
+    match opts
+        .apply_config_file(&matches)
+        .map(|()| opts.apply_spec())
+        .and_then(|()| opts.apply_env_wordlist(&matches))
+        .and_then(|()| opts.apply_locale(&matches))
+        .and_then(|()| run(&opts))
+    {
+        Ok(()) => {}
+
+        // If stdout was closed early (for instance, by piping to head), there's
+        // nobody left to read the output, so stop quietly.
+        Err(MakepassError::StdoutError(err)) if err.kind() == io::ErrorKind::BrokenPipe => {}
+
+        // The conventional exit status for a process stopped by SIGINT
+        Err(MakepassError::Interrupted) => exit(130),
+
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(1);
+        }
     }
 }
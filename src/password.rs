@@ -1,104 +1,1458 @@
-use std::fmt::{self, Display, Formatter};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter, Write as _};
 use std::iter;
+use std::ptr;
 
+use lazy_format::lazy_format;
 use rand::seq::{IteratorRandom, SliceRandom};
 use rand::{CryptoRng, Rng};
+use thiserror::Error;
+#[cfg(feature = "graphemes")]
+use unicode_segmentation::UnicodeSegmentation;
 
-use crate::util::Len;
+use crate::exact_length::ExactLength;
+use crate::pronounceable;
+use crate::util::{Encoding, Len};
+use crate::word_weights::WordWeights;
 
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// Where the words in a password come from
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WordSource<'a> {
+    /// Non-repeating words chosen from a wordlist
+    Wordlist(&'a [&'a str]),
+
+    /// Pronounceable pseudo-words, each with the given number of syllables
+    Pronounceable { syllables: usize },
+
+    /// Each word is chosen from its own wordlist (for instance, to follow a
+    /// pattern like adjective-noun-verb-noun). Words are non-repeating among
+    /// slots that share the same wordlist slice. Fixed words don't have
+    /// slots.
+    Categorized(&'a [&'a [&'a str]]),
+}
+
+/// What goes between the words of a password
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Separator<'a> {
+    /// The words are run together
+    #[default]
+    None,
+
+    /// The same string goes between each pair of words
+    Fixed(&'a str),
+
+    /// A character is chosen at random from the set for each gap
+    Random(&'a str),
+}
+
+/// Where the symbols go in a password
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum SymbolPosition {
+    /// Before the first word
+    Start,
+
+    /// After the numeral
+    #[default]
+    End,
+
+    /// `symbol_count` symbols at each end of the password
+    Both,
+}
+
+named_enum! {
+    pub SymbolPosition, InvalidSymbolPosition("symbol position") {
+        "start" => Start,
+        "end" => End,
+        "both" => Both,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct PasswordRules<'a> {
-    pub wordlist: &'a [&'a str],
+    pub source: WordSource<'a>,
     pub num_words: usize,
+    pub separator: Separator<'a>,
     pub append_numeral: bool,
+
+    /// The base of the appended numeral, from 2 to 36. Digits past 9 are
+    /// rendered as lowercase letters.
+    pub numeral_base: u8,
+
     pub append_symbol: Option<&'a str>,
+
+    /// The number of symbols to append, if `append_symbol` is set. Each one is
+    /// chosen independently, so they may repeat.
+    pub symbol_count: usize,
+
+    /// Where the symbols go. With `SymbolPosition::Both`, there are
+    /// `symbol_count` symbols at each end.
+    pub symbol_position: SymbolPosition,
+
+    /// If given, words are selected such that the password has an exact
+    /// length; see `plan_exact_length`.
+    pub exact_length: Option<ExactLength<'a>>,
+
+    /// Words that are always placed at particular (0-based) positions in the
+    /// password, sorted by position. These count towards `num_words`, but
+    /// they're known to the attacker, so they contribute no entropy.
+    pub fixed_words: &'a [(usize, &'a str)],
+
+    /// If set, a checksum character is appended to the password; see
+    /// `checksum`. It's derived from the rest of the password, so it
+    /// contributes no entropy.
+    pub append_checksum: bool,
+
+    /// If set, the random words are sorted alphabetically. This loses the
+    /// entropy of their order; see `order_entropy`.
+    pub sort_words: bool,
+
+    /// If set, `num_words` is ignored when generating passwords with a
+    /// maximum length; instead, words are added one at a time for as long as
+    /// the password still fits. See `gen_password_fitting`.
+    pub fit_length: bool,
+
+    /// If given, words from a `WordSource::Wordlist` are chosen with these
+    /// weights (which must be for that wordlist) instead of uniformly. This
+    /// reduces the entropy; see `WordWeights::entropy`. It's ignored with
+    /// `exact_length`, which has its own selection.
+    pub word_weights: Option<&'a WordWeights>,
+
+    /// How the letters of the words are capitalized
+    pub case: WordCase,
+}
+
+/// How the letters of the words in a password are capitalized
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum WordCase {
+    /// As they're written in the wordlist
+    #[default]
+    AsIs,
+
+    /// Each letter is randomly uppercase or lowercase. Letters whose
+    /// uppercase and lowercase forms have different lengths (like "ß") are
+    /// left alone, so that this never changes the length of a word.
+    Random,
+
+    /// Every letter is uppercase. This adds no entropy.
+    Upper,
+
+    /// Every letter is lowercase. This adds no entropy.
+    Lower,
+
+    /// The first letter of each word is uppercase, and the rest are
+    /// lowercase. This adds no entropy.
+    Title,
+
+    /// Like `Title`, except that the first word is all lowercase. This adds
+    /// no entropy.
+    Camel,
+}
+
+/// Check if a character can be flipped between uppercase and lowercase
+/// without changing its length. See `WordCase::Random`.
+fn has_case(c: char) -> bool {
+    let mut upper = c.to_uppercase();
+    let mut lower = c.to_lowercase();
+
+    match (upper.next(), upper.next(), lower.next(), lower.next()) {
+        (Some(upper), None, Some(lower), None) => {
+            upper != lower && upper.len_utf8() == lower.len_utf8()
+        }
+        _ => false,
+    }
+}
+
+/// Capitalize the first letter of a word, and lowercase the rest; see
+/// `WordCase::Title`
+fn title_case(word: &str) -> String {
+    let mut chars = word.chars();
+
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.as_str().to_lowercase().chars())
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// Randomly capitalize each letter of a word; see `WordCase::Random`
+fn randomize_case<R: CryptoRng + Rng + ?Sized>(rng: &mut R, word: &str) -> String {
+    word.chars()
+        .map(|c| match has_case(c) && rng.gen() {
+            true if c.is_uppercase() => c.to_lowercase().next().unwrap_or(c),
+            true => c.to_uppercase().next().unwrap_or(c),
+            false => c,
+        })
+        .collect()
+}
+
+/// A reason that a set of `PasswordRules` can't be used to generate
+/// passwords; see `PasswordRules::validate`.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum RulesError {
+    #[error("the wordlist is empty")]
+    EmptyWordlist,
+
+    #[error("{needed} words are needed, but the wordlist only has {available}")]
+    NotEnoughWords { needed: usize, available: usize },
+
+    #[error("a category needs {needed} words, but it only has {available}")]
+    NotEnoughCategoryWords { needed: usize, available: usize },
+
+    #[error("the pattern has {slots} slots, but {needed} random words are needed")]
+    WrongSlotCount { slots: usize, needed: usize },
+
+    #[error("pronounceable words must have at least one syllable")]
+    NoSyllables,
+
+    #[error("the symbol set is empty")]
+    EmptySymbolSet,
+
+    #[error("the separator set is empty")]
+    EmptySeparatorSet,
+
+    #[error("the numeral base must be between 2 and 36, not {0}")]
+    InvalidNumeralBase(u8),
+
+    #[error("a word is fixed at position {position}, but there are only {num_words} words")]
+    FixedWordOutOfRange { position: usize, num_words: usize },
+
+    #[error("the password would be empty, with no words, numeral, symbols, or checksum")]
+    EmptyPassword,
 }
 
 impl<'a> PasswordRules<'a> {
-    fn gen_words<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> impl Iterator<Item = &'a str> {
-        self.wordlist.choose_multiple(rng, self.num_words).cloned()
+    /// Check that passwords can actually be generated with these rules.
+    /// Generating passwords with rules that fail this check may panic, or
+    /// produce passwords that don't match the rules (and entropy estimates
+    /// that are wrong).
+    pub fn validate(&self) -> Result<(), RulesError> {
+        if let Some(&(position, _)) = self
+            .fixed_words
+            .iter()
+            .find(|&&(position, _)| position >= self.num_words)
+        {
+            return Err(RulesError::FixedWordOutOfRange {
+                position: position + 1,
+                num_words: self.num_words,
+            });
+        }
+
+        match self.source {
+            WordSource::Wordlist([]) => return Err(RulesError::EmptyWordlist),
+            // With fit_length, num_words is ignored; it's fine to use fewer
+            WordSource::Wordlist(wordlist)
+                if !self.fit_length && wordlist.len() < self.num_random_words() =>
+            {
+                return Err(RulesError::NotEnoughWords {
+                    needed: self.num_random_words(),
+                    available: wordlist.len(),
+                })
+            }
+            WordSource::Wordlist(_) => {}
+            WordSource::Pronounceable { syllables: 0 } => return Err(RulesError::NoSyllables),
+            WordSource::Pronounceable { .. } => {}
+            WordSource::Categorized(slots) => {
+                if slots.len() != self.num_random_words() {
+                    return Err(RulesError::WrongSlotCount {
+                        slots: slots.len(),
+                        needed: self.num_random_words(),
+                    });
+                }
+
+                for &category in slots {
+                    let needed = slots
+                        .iter()
+                        .filter(|&&slot| ptr::eq(slot, category))
+                        .count();
+
+                    if category.len() < needed {
+                        return Err(RulesError::NotEnoughCategoryWords {
+                            needed,
+                            available: category.len(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if self.append_symbol == Some("") {
+            return Err(RulesError::EmptySymbolSet);
+        }
+
+        if self.separator == Separator::Random("") {
+            return Err(RulesError::EmptySeparatorSet);
+        }
+
+        if !(2..=36).contains(&self.numeral_base) {
+            return Err(RulesError::InvalidNumeralBase(self.numeral_base));
+        }
+
+        if self.num_words == 0
+            && !self.fit_length
+            && !self.append_numeral
+            && self.num_symbols() == 0
+            && !self.append_checksum
+        {
+            return Err(RulesError::EmptyPassword);
+        }
+
+        Ok(())
+    }
+
+    /// The number of words in the password that are chosen randomly, rather
+    /// than fixed.
+    fn num_random_words(&self) -> usize {
+        self.num_words - self.fixed_words.len()
+    }
+
+    /// Sort the random words, if `sort_words` is set, then insert the fixed
+    /// words into their positions among them.
+    fn arrange_words(&self, words: &mut Vec<Cow<'a, str>>) {
+        if self.sort_words {
+            words.sort_unstable();
+        }
+
+        // Because the fixed words are sorted by position, each one is
+        // inserted after all of the fixed words that precede it.
+        for &(position, word) in self.fixed_words {
+            words.insert(position, Cow::Borrowed(word));
+        }
+    }
+
+    fn gen_words<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Vec<Cow<'a, str>> {
+        let mut words = self.gen_random_words(rng);
+        self.arrange_words(&mut words);
+        words
+    }
+
+    fn gen_random_words<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Vec<Cow<'a, str>> {
+        if let Some(ref plan) = self.exact_length {
+            return plan.gen_words(rng).into_iter().map(Cow::Borrowed).collect();
+        }
+
+        match (self.source, self.word_weights) {
+            (WordSource::Wordlist(wordlist), Some(weights)) => weights
+                .choose_distinct(rng, self.num_random_words())
+                .into_iter()
+                .map(|index| Cow::Borrowed(wordlist[index]))
+                .collect(),
+            (WordSource::Wordlist(wordlist), None) => wordlist
+                .choose_multiple(rng, self.num_random_words())
+                .map(|&word| Cow::Borrowed(word))
+                .collect(),
+            (WordSource::Pronounceable { syllables }, _) => (0..self.num_random_words())
+                .map(|_| Cow::Owned(pronounceable::gen_word(rng, syllables)))
+                .collect(),
+            (WordSource::Categorized(slots), _) => {
+                let mut words = vec![None; slots.len()];
+
+                // Fill all the slots that share a category at once, so that
+                // they don't repeat words
+                for (i, &category) in slots.iter().enumerate() {
+                    if words[i].is_some() {
+                        continue;
+                    }
+
+                    let positions: Vec<usize> = (i..slots.len())
+                        .filter(|&j| ptr::eq(slots[j], category))
+                        .collect();
+
+                    category
+                        .choose_multiple(rng, positions.len())
+                        .zip(positions)
+                        .for_each(|(&word, position)| words[position] = Some(Cow::Borrowed(word)));
+                }
+
+                words
+                    .into_iter()
+                    .map(|word| word.expect("category smaller than its number of slots"))
+                    .collect()
+            }
+        }
+    }
+
+    /// The total number of symbols in the password, at either end
+    pub fn num_symbols(&self) -> usize {
+        match self.append_symbol {
+            None => 0,
+            Some(_) if self.symbol_position == SymbolPosition::Both => self.symbol_count * 2,
+            Some(_) => self.symbol_count,
+        }
+    }
+
+    /// Generate the symbols for one end of the password, if symbols go at
+    /// that end.
+    fn gen_symbols<R: CryptoRng + Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        position: SymbolPosition,
+    ) -> Vec<&'a str> {
+        match self.append_symbol {
+            Some(symbol_set)
+                if self.symbol_position == position
+                    || self.symbol_position == SymbolPosition::Both =>
+            {
+                (0..self.symbol_count)
+                    .filter_map(|_| split_symbols(symbol_set).choose(rng))
+                    .collect()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// The number of gaps between words, each of which gets a separator
+    fn num_gaps(&self) -> usize {
+        self.num_words.saturating_sub(1)
+    }
+
+    fn gen_separators<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Vec<&'a str> {
+        (0..self.num_gaps())
+            .map_while(|_| self.gen_separator(rng))
+            .collect()
+    }
+
+    /// Choose the separator for one gap between words, or None if the words
+    /// are run together
+    fn gen_separator<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Option<&'a str> {
+        match self.separator {
+            Separator::None => None,
+            Separator::Fixed(separator) => Some(separator),
+            Separator::Random(set) => set
+                .char_indices()
+                .choose(rng)
+                .map(|(index, c)| &set[index..index + c.len_utf8()]),
+        }
+    }
+
+    fn gen_numeral<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Option<Numeral> {
+        self.append_numeral.then(|| Numeral {
+            value: rng.gen_range(0..self.numeral_base),
+            base: self.numeral_base,
+        })
     }
 
-    fn gen_symbol<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Option<char> {
-        self.append_symbol?.chars().choose(rng)
+    /// Assemble a password from its words, adding the separators, numeral,
+    /// symbol, and checksum.
+    fn assemble<R: CryptoRng + Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        words: Vec<Cow<'a, str>>,
+    ) -> Password<'a> {
+        let mut password = Password {
+            words: self.apply_case(rng, words),
+            leading_symbols: self.gen_symbols(rng, SymbolPosition::Start),
+            separators: self.gen_separators(rng),
+            numeral: self.gen_numeral(rng),
+            symbols: self.gen_symbols(rng, SymbolPosition::End),
+            checksum: None,
+        };
+
+        if self.append_checksum {
+            password.checksum = Some(checksum(&password.to_string()));
+        }
+
+        password
     }
 
-    fn gen_numeral<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Option<u8> {
-        self.append_numeral.then(|| rng.gen_range(0..10))
+    /// Capitalize the (arranged) words according to `case`
+    fn apply_case<R: CryptoRng + Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        words: Vec<Cow<'a, str>>,
+    ) -> Vec<Cow<'a, str>> {
+        match self.case {
+            WordCase::AsIs => words,
+            WordCase::Random => words
+                .iter()
+                .map(|word| Cow::Owned(randomize_case(rng, word)))
+                .collect(),
+            WordCase::Upper => words
+                .iter()
+                .map(|word| Cow::Owned(word.to_uppercase()))
+                .collect(),
+            WordCase::Lower => words
+                .iter()
+                .map(|word| Cow::Owned(word.to_lowercase()))
+                .collect(),
+            WordCase::Title => words
+                .iter()
+                .map(|word| Cow::Owned(title_case(word)))
+                .collect(),
+            WordCase::Camel => words
+                .iter()
+                .enumerate()
+                .map(|(index, word)| match index {
+                    0 => Cow::Owned(word.to_lowercase()),
+                    _ => Cow::Owned(title_case(word)),
+                })
+                .collect(),
+        }
     }
 
     pub fn gen_password<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> Password<'a> {
-        Password {
-            words: self.gen_words(rng).collect(),
+        let words = self.gen_words(rng);
+        self.assemble(rng, words)
+    }
+
+    /// Generate a password, giving up early and returning None as soon as the
+    /// words chosen so far are longer than `max_length`. This is cheaper than
+    /// building the whole password and rejecting it when the maximum length
+    /// is tight. The passwords that are returned have the same distribution as
+    /// those from `gen_password`, since the abandoned ones would have been
+    /// rejected anyway.
+    pub fn gen_password_within<R: CryptoRng + Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        max_length: usize,
+        encoding: Encoding,
+    ) -> Option<Password<'a>> {
+        if self.fit_length {
+            return self.gen_password_fitting(rng, max_length, encoding);
+        }
+
+        let num_words = self.num_random_words();
+
+        let wordlist = match (self.source, &self.exact_length) {
+            (WordSource::Wordlist(wordlist), None) if num_words <= wordlist.len() => wordlist,
+            _ => return Some(self.gen_password(rng)),
+        };
+
+        // Select non-repeating words one at a time, so that we can stop as
+        // soon as the password is too long.
+        let mut indexes = Vec::with_capacity(num_words);
+        let mut length: usize = self
+            .fixed_words
+            .iter()
+            .map(|&(_, word)| encoding.measure(word))
+            .sum();
+
+        if length > max_length {
+            return None;
+        }
+
+        while indexes.len() < num_words {
+            let index = match self.word_weights {
+                Some(weights) => weights.choose_index(rng),
+                None => rng.gen_range(0..wordlist.len()),
+            };
+
+            if indexes.contains(&index) {
+                continue;
+            }
+
+            length += encoding.measure(wordlist[index]);
+
+            if length > max_length {
+                return None;
+            }
+
+            indexes.push(index);
+        }
+
+        let mut words = indexes
+            .into_iter()
+            .map(|index| Cow::Borrowed(wordlist[index]))
+            .collect();
+
+        self.arrange_words(&mut words);
+
+        Some(self.assemble(rng, words))
+    }
+
+    /// Generate a password with as many words as will fit in `max_length`.
+    /// The numeral and symbols are chosen first, then random words (with
+    /// their separators) are added one at a time, keeping a running length,
+    /// until the next word would make it too long. Returns None if not even
+    /// one word fits. Only wordlist sources (without fixed
+    /// words) are supported; other sources fall back to `gen_password`.
+    ///
+    /// Because the number of words varies, so does the entropy of each
+    /// password; use `with_num_words` to get rules that describe a particular
+    /// password.
+    fn gen_password_fitting<R: CryptoRng + Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        max_length: usize,
+        encoding: Encoding,
+    ) -> Option<Password<'a>> {
+        let wordlist = match self.source {
+            WordSource::Wordlist(wordlist) if self.fixed_words.is_empty() => wordlist,
+            _ => return Some(self.gen_password(rng)),
+        };
+
+        let mut password = Password {
+            leading_symbols: self.gen_symbols(rng, SymbolPosition::Start),
+            words: Vec::new(),
+            separators: Vec::new(),
             numeral: self.gen_numeral(rng),
-            symbol: self.gen_symbol(rng),
+            symbols: self.gen_symbols(rng, SymbolPosition::End),
+            checksum: None,
+        };
+
+        // The checksum is always a single ASCII character
+        let mut length = encoding.measure(&password) + usize::from(self.append_checksum);
+        let mut words: Vec<&'a str> = Vec::new();
+
+        while words.len() < wordlist.len() {
+            let word = match self.word_weights {
+                Some(weights) => wordlist[weights.choose_index(rng)],
+                None => *wordlist.choose(rng).expect("wordlist isn't empty"),
+            };
+
+            if words.contains(&word) {
+                continue;
+            }
+
+            let separator = match words.is_empty() {
+                true => None,
+                false => self.gen_separator(rng),
+            };
+
+            length += encoding.measure(word)
+                + separator.map_or(0, |separator| encoding.measure(separator));
+
+            if length > max_length {
+                break;
+            }
+
+            words.push(word);
+            password.separators.extend(separator);
+        }
+
+        // The running length is of the words before their case is changed,
+        // which (rarely) changes their length, so check the whole password,
+        // and drop words until it fits
+        while !words.is_empty() {
+            let mut arranged = words.iter().copied().map(Cow::Borrowed).collect();
+            self.arrange_words(&mut arranged);
+
+            password.words = self.apply_case(rng, arranged);
+            password.checksum = None;
+            password.checksum = self
+                .append_checksum
+                .then(|| checksum(&password.to_string()));
+
+            if encoding.measure(&password) <= max_length {
+                return Some(password);
+            }
+
+            words.pop();
+            password.separators.pop();
+        }
+
+        None
+    }
+
+    /// A copy of these rules, with a different number of words. This is used
+    /// to describe (and estimate the entropy of) passwords from
+    /// `gen_password_fitting`.
+    pub fn with_num_words(&self, num_words: usize) -> Self {
+        PasswordRules {
+            num_words,
+            ..self.clone()
         }
     }
 
-    pub fn stream_passwords<'s, R: CryptoRng + Rng + ?Sized>(
+    /// Create an endless stream of attempts at generating a password no
+    /// longer than `max_length`; see `gen_password_within`. Each attempt that
+    /// was abandoned early is None.
+    pub fn stream_passwords_within<'s, R: CryptoRng + Rng + ?Sized>(
         &'s self,
         rng: &'s mut R,
-    ) -> impl Iterator<Item = Password<'a>> + 's {
-        iter::repeat_with(move || self.gen_password(rng))
+        max_length: usize,
+        encoding: Encoding,
+    ) -> impl Iterator<Item = Option<Password<'a>>> + 's {
+        iter::repeat_with(move || self.gen_password_within(rng, max_length, encoding))
     }
 
+    /// Plan a selection of words such that passwords generated with these
+    /// rules will have exactly `length` bytes (or characters). Returns None if
+    /// this isn't possible; in particular, it's only possible with a wordlist
+    /// and if all the possible symbols (and separators) have the same length.
+    /// If this returns
+    /// None, callers should fall back to rejecting passwords of the wrong
+    /// length.
+    pub fn plan_exact_length(&self, length: usize, encoding: Encoding) -> Option<ExactLength<'a>> {
+        let wordlist = match self.source {
+            WordSource::Wordlist(wordlist) => wordlist,
+            WordSource::Pronounceable { .. } | WordSource::Categorized(..) => return None,
+        };
+
+        let numeral_len = match self.append_numeral {
+            true => Numeral::uniform_digits(self.numeral_base)?,
+            false => 0,
+        };
+        let checksum_len = if self.append_checksum { 1 } else { 0 };
+
+        let symbol_len = match self.append_symbol {
+            None => 0,
+            Some(symbol_set) => {
+                uniform_len(split_symbols(symbol_set), encoding)? * self.num_symbols()
+            }
+        };
+
+        let separator_len = match self.separator {
+            Separator::None => 0,
+            Separator::Fixed(separator) => encoding.measure(separator) * self.num_gaps(),
+            Separator::Random(set) => {
+                uniform_len(set.split_inclusive(|_| true), encoding)? * self.num_gaps()
+            }
+        };
+
+        let fixed_len: usize = self
+            .fixed_words
+            .iter()
+            .map(|&(_, word)| encoding.measure(word))
+            .sum();
+
+        let words_length = length
+            .checked_sub(numeral_len + symbol_len + separator_len + checksum_len + fixed_len)?;
+        ExactLength::new(wordlist, self.num_random_words(), words_length, encoding)
+    }
+
+    /// The entropy of the random words, less the entropy lost to sorting them
     pub fn words_entropy(&self) -> f32 {
-        (0..self.num_words)
-            .map(|i| {
-                self.wordlist
-                    .len()
-                    .checked_sub(i)
-                    .expect("num_words larger than wordset size")
-            })
-            .map(|n| (n as f32).log2())
-            .sum()
+        (self.ordered_words_entropy() - self.order_entropy()).max(0.0)
+    }
+
+    /// The entropy carried by the order of the random words: log2 of the
+    /// number of ways to order them. This is lost if they're sorted, so it's
+    /// 0 unless `sort_words` is set. For sources where the words may repeat,
+    /// or can be told apart by their category, less entropy than this is
+    /// actually lost, so this is conservative.
+    pub fn order_entropy(&self) -> f32 {
+        if self.sort_words {
+            (2..=self.num_random_words())
+                .map(|n| (n as f32).log2())
+                .sum()
+        } else {
+            0f32
+        }
+    }
+
+    fn ordered_words_entropy(&self) -> f32 {
+        if let Some(ref plan) = self.exact_length {
+            return plan.entropy();
+        }
+
+        match (self.source, self.word_weights) {
+            (WordSource::Wordlist(_), Some(weights)) => weights.entropy(self.num_random_words()),
+            (WordSource::Wordlist(wordlist), None) => (0..self.num_random_words())
+                .map(|i| {
+                    wordlist
+                        .len()
+                        .checked_sub(i)
+                        .expect("num_words larger than wordset size")
+                })
+                .map(|n| (n as f32).log2())
+                .sum(),
+            (WordSource::Pronounceable { syllables }, _) => {
+                (self.num_random_words() * syllables) as f32 * pronounceable::syllable_entropy()
+            }
+            (WordSource::Categorized(slots), _) => slots
+                .iter()
+                .enumerate()
+                .map(|(i, &category)| {
+                    let earlier = slots[..i]
+                        .iter()
+                        .filter(|&&slot| ptr::eq(slot, category))
+                        .count();
+
+                    category
+                        .len()
+                        .checked_sub(earlier)
+                        .expect("category smaller than its number of slots")
+                })
+                .map(|n| (n as f32).log2())
+                .sum(),
+        }
     }
 
     pub fn numeral_entropy(&self) -> f32 {
         if self.append_numeral {
-            (10f32).log2()
+            (self.numeral_base as f32).log2()
         } else {
             0f32
         }
     }
 
+    /// The entropy of choosing a single random word from its pool: the
+    /// smallest pool, if there's more than one. This ignores that words don't
+    /// repeat, which makes later words slightly weaker.
+    pub fn single_word_entropy(&self) -> f32 {
+        match (self.source, self.word_weights) {
+            (WordSource::Wordlist(_), Some(weights)) => weights.entropy(1),
+            (WordSource::Wordlist(wordlist), None) => (wordlist.len() as f32).log2(),
+            (WordSource::Pronounceable { syllables }, _) => {
+                syllables as f32 * pronounceable::syllable_entropy()
+            }
+            (WordSource::Categorized(slots), _) => slots
+                .iter()
+                .map(|slot| (slot.len() as f32).log2())
+                .fold(f32::INFINITY, f32::min),
+        }
+    }
+
+    /// The entropy from randomizing the case of the letters in a password's
+    /// words: one bit per letter (with `WordCase::Random`). Digits, symbols,
+    /// and letters without case don't count. This depends on the length of
+    /// the words, so it's only known for a particular password.
+    pub fn case_entropy(&self, password: &Password) -> f32 {
+        match self.case {
+            WordCase::AsIs
+            | WordCase::Upper
+            | WordCase::Lower
+            | WordCase::Title
+            | WordCase::Camel => 0f32,
+            WordCase::Random => password
+                .words()
+                .flat_map(|word| word.chars())
+                .filter(|&c| has_case(c))
+                .count() as f32,
+        }
+    }
+
+    pub fn separator_entropy(&self) -> f32 {
+        match self.separator {
+            Separator::None | Separator::Fixed(_) => 0f32,
+            Separator::Random(set) => self.num_gaps() as f32 * (set.chars().count() as f32).log2(),
+        }
+    }
+
     pub fn symbol_entropy(&self) -> f32 {
         match self.append_symbol {
             None => 0f32,
-            Some(symbol_set) => (symbol_set.chars().count() as f32).log2(),
+            Some(symbol_set) => {
+                self.num_symbols() as f32 * (split_symbols(symbol_set).count() as f32).log2()
+            }
+        }
+    }
+
+    /// The total entropy of a password generated with these rules. Unlike
+    /// the estimate that makepass prints, this doesn't include any adjustment
+    /// for passwords that were rejected by constraints, since that depends on
+    /// sampling. In debug builds, this panics if the password couldn't have
+    /// come from these rules.
+    pub fn password_entropy(&self, password: &Password) -> f32 {
+        debug_assert!(
+            self.could_generate(password),
+            "password {:?} doesn't match its rules",
+            password
+        );
+
+        // With fit_length, the number of words varies from password to password
+        let fitted;
+        let rules = match self.fit_length {
+            true => {
+                fitted = self.with_num_words(password.words.len());
+                &fitted
+            }
+            false => self,
+        };
+
+        rules.words_entropy()
+            + rules.separator_entropy()
+            + rules.numeral_entropy()
+            + rules.symbol_entropy()
+            + rules.case_entropy(password)
+    }
+
+    /// Check if a password has the shape that these rules would give it
+    fn could_generate(&self, password: &Password) -> bool {
+        let num_symbols = password.leading_symbols.len() + password.symbols.len();
+
+        (self.fit_length || password.words.len() == self.num_words)
+            && password.numeral.is_some() == self.append_numeral
+            && num_symbols == self.num_symbols()
+            && password.checksum.is_some() == self.append_checksum
+            && self
+                .fixed_words
+                .iter()
+                .all(|&(position, word)| match self.case {
+                    WordCase::AsIs => password.words.get(position).map(Cow::as_ref) == Some(word),
+                    _ => password.words.get(position).is_some(),
+                })
+    }
+}
+
+/// A number, in some base. Currently this is always a single digit, but
+/// nothing here depends on that.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Numeral {
+    value: u8,
+    base: u8,
+}
+
+impl Numeral {
+    /// The most digits a numeral can have: a u8 in base 2
+    const MAX_DIGITS: usize = 8;
+
+    /// Render the digits of the numeral into `buffer`, returning the number
+    /// of digits, which are at the end of the buffer.
+    fn render(&self, buffer: &mut [u8; Numeral::MAX_DIGITS]) -> usize {
+        let base = u32::from(self.base);
+        let mut value = u32::from(self.value);
+        let mut count = 0;
+
+        loop {
+            count += 1;
+            buffer[Numeral::MAX_DIGITS - count] = char::from_digit(value % base, base)
+                .expect("a remainder is always a valid digit in its base")
+                as u8;
+            value /= base;
+
+            if value == 0 {
+                break count;
+            }
         }
     }
+
+    /// The number of digits in the numeral
+    pub fn num_digits(&self) -> usize {
+        self.render(&mut [0; Numeral::MAX_DIGITS])
+    }
+
+    /// The number of digits in every numeral in `base` (which must be valid),
+    /// or None if they don't all have the same number. Digits are ASCII, so
+    /// this is their length in any encoding.
+    fn uniform_digits(base: u8) -> Option<usize> {
+        let smallest = Numeral { value: 0, base }.num_digits();
+        let largest = Numeral {
+            value: base - 1,
+            base,
+        }
+        .num_digits();
+
+        (smallest == largest).then_some(largest)
+    }
+}
+
+impl Display for Numeral {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut buffer = [0; Numeral::MAX_DIGITS];
+        let count = self.render(&mut buffer);
+
+        f.write_str(
+            std::str::from_utf8(&buffer[Numeral::MAX_DIGITS - count..])
+                .expect("digits are always ASCII"),
+        )
+    }
 }
 
 /// Struct type for a password
 #[derive(Debug, PartialEq, Eq, Clone, Default)]
 pub struct Password<'a> {
-    words: Vec<&'a str>,
-    numeral: Option<u8>,
-    symbol: Option<char>,
+    /// Symbols before the first word, with `SymbolPosition::Start` or `Both`
+    leading_symbols: Vec<&'a str>,
+
+    words: Vec<Cow<'a, str>>,
+
+    /// The separators between the words; there's either one for each gap, or
+    /// none at all.
+    separators: Vec<&'a str>,
+
+    numeral: Option<Numeral>,
+    symbols: Vec<&'a str>,
+    checksum: Option<char>,
 }
 
+/// Split a symbol set into its individual symbols. With the `graphemes`
+/// feature, each symbol is a whole grapheme cluster, so that an emoji made of
+/// several code points is a single symbol; otherwise, each symbol is a single
+/// `char`.
+pub fn split_symbols(symbol_set: &str) -> impl Iterator<Item = &str> + Clone {
+    #[cfg(feature = "graphemes")]
+    {
+        symbol_set.graphemes(true)
+    }
+
+    #[cfg(not(feature = "graphemes"))]
+    {
+        symbol_set.split_inclusive(|_| true)
+    }
+}
+
+/// If every item has the same length, return that length.
+fn uniform_len<'s>(items: impl Iterator<Item = &'s str>, encoding: Encoding) -> Option<usize> {
+    let mut lengths = items.map(|item| encoding.measure(item));
+
+    let first = lengths.next()?;
+    lengths.all(|len| len == first).then_some(first)
+}
+
+/// Compute the checksum character of a rendered password: a position-weighted
+/// sum of its bytes, modulo 36, as a base 36 digit (0-9 or a-z). Because of
+/// the weights, swapping two characters usually changes the checksum.
+pub fn checksum(password: &str) -> char {
+    let sum = password
+        .bytes()
+        .zip(1..)
+        .fold(0, |sum, (byte, weight)| (sum + byte as u32 * weight) % 36);
+
+    char::from_digit(sum, 36).expect("checksum is less than 36")
+}
+
+/// A single part of a rendered password
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Component<'a> {
+    Word(&'a str),
+    Separator(&'a str),
+    Numeral(Numeral),
+    Symbol(&'a str),
+    Checksum(char),
+}
+
+impl Len for Component<'_> {
+    fn len(&self) -> usize {
+        match *self {
+            Component::Word(word) => word.len(),
+            Component::Separator(separator) => separator.len(),
+            // Digits are always ASCII, so each is one byte
+            Component::Numeral(numeral) => numeral.num_digits(),
+            Component::Symbol(symbol) => symbol.len(),
+            Component::Checksum(checksum) => checksum.len_utf8(),
+        }
+    }
+
+    fn char_len(&self) -> usize {
+        match *self {
+            Component::Word(word) => word.chars().count(),
+            Component::Separator(separator) => separator.chars().count(),
+            Component::Numeral(numeral) => numeral.num_digits(),
+            Component::Symbol(symbol) => symbol.chars().count(),
+            Component::Checksum(_) => 1,
+        }
+    }
+}
+
+impl Display for Component<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Component::Word(word) => word.fmt(f),
+            Component::Separator(separator) => separator.fmt(f),
+            Component::Numeral(numeral) => numeral.fmt(f),
+            Component::Symbol(symbol) => symbol.fmt(f),
+            Component::Checksum(checksum) => checksum.fmt(f),
+        }
+    }
+}
+
+impl<'a> Password<'a> {
+    /// Iterate over the parts of this password, in the order they're
+    /// rendered.
+    pub fn components(&self) -> impl Iterator<Item = Component<'_>> {
+        // Each word after the first is preceded by its separator, if any
+        let separators = iter::once(None)
+            .chain(self.separators.iter().map(Some))
+            .chain(iter::repeat(None));
+
+        let leading_symbols = self.leading_symbols.iter().copied().map(Component::Symbol);
+
+        let words = self
+            .words
+            .iter()
+            .zip(separators)
+            .flat_map(|(word, separator)| {
+                separator
+                    .map(|&separator| Component::Separator(separator))
+                    .into_iter()
+                    .chain(iter::once(Component::Word(word)))
+            });
+
+        leading_symbols
+            .chain(words)
+            .chain(self.numeral.map(Component::Numeral))
+            .chain(self.symbols.iter().copied().map(Component::Symbol))
+            .chain(self.checksum.map(Component::Checksum))
+    }
+
+    /// Iterate over just the words in this password.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(|word| word.as_ref())
+    }
+
+    /// The first letter of each word, as a mnemonic. The letters keep the
+    /// case they have in the password, so they follow the `WordCase`.
+    pub fn acronym(&self) -> String {
+        self.words()
+            .filter_map(|word| word.chars().next())
+            .collect()
+    }
+
+    /// A key that's the same for passwords that are the same passphrase, even
+    /// if they aren't equal: the words are lowercased and sorted, the symbols
+    /// (from either end) are sorted, and the separators and checksum are left
+    /// out. Unlike `PartialEq`, this ignores the order of the words, so it's
+    /// suitable for finding duplicates with `sort_words`.
+    pub fn canonical_key(&self) -> String {
+        let mut words: Vec<String> = self.words().map(str::to_lowercase).collect();
+        words.sort_unstable();
+
+        let mut symbols: Vec<&str> = self
+            .leading_symbols
+            .iter()
+            .chain(&self.symbols)
+            .copied()
+            .collect();
+        symbols.sort_unstable();
+
+        // Words may contain spaces (see --word-separator), so the parts of the
+        // key are separated with NUL instead, which no real wordlist has
+        format!(
+            "{}\0{}\0{}",
+            words.join("\0"),
+            lazy_format!(match (self.numeral) {
+                Some(numeral) => "{numeral}",
+                None => "",
+            }),
+            symbols.concat(),
+        )
+    }
+
+    /// Append this password to `buffer`. Unlike `to_string`, this lets one
+    /// buffer be reused for many passwords (clearing it between them), so
+    /// that nothing is allocated once the buffer is large enough.
+    pub fn write_into(&self, buffer: &mut String) {
+        buffer.reserve(self.len());
+
+        for component in self.components() {
+            match component {
+                Component::Word(text) | Component::Separator(text) | Component::Symbol(text) => {
+                    buffer.push_str(text)
+                }
+                Component::Checksum(c) => buffer.push(c),
+                Component::Numeral(numeral) => {
+                    write!(buffer, "{}", numeral).expect("writing to a String can't fail")
+                }
+            }
+        }
+    }
+
+    /// Display just the words in this password, joined with `separator`
+    /// (rather than the password's own separators).
+    pub fn display_words<'s>(&'s self, separator: &'s str) -> impl Display + 's {
+        WordsDisplay {
+            password: self,
+            separator,
+        }
+    }
+}
+
+struct WordsDisplay<'s, 'a> {
+    password: &'s Password<'a>,
+    separator: &'s str,
+}
+
+impl Display for WordsDisplay<'_, '_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.password
+            .words()
+            .enumerate()
+            .try_for_each(|(index, word)| match index {
+                0 => word.fmt(f),
+                _ => write!(f, "{}{}", self.separator, word),
+            })
+    }
+}
+
+/// The length of a password is the total length of all of its components, so
+/// it's always the length of the rendered password.
 impl<'a> Len for Password<'a> {
     fn len(&self) -> usize {
-        // FIXME: ensure that numeral is indeed a single character numeral
-        self.words.iter().map(move |word| word.len()).sum::<usize>()
-            + self.numeral.map(|_| 1).unwrap_or(0)
-            + self.symbol.map(|c| c.len_utf8()).unwrap_or(0)
+        self.components().map(|component| component.len()).sum()
+    }
+
+    fn char_len(&self) -> usize {
+        self.components()
+            .map(|component| component.char_len())
+            .sum()
     }
 }
 
 impl<'a> Display for Password<'a> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        self.words.iter().try_for_each(|word| word.fmt(f))?;
+        self.components().try_for_each(|component| component.fmt(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
 
-        if let Some(numeral) = self.numeral {
-            numeral.fmt(f)?;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    const WORDLIST: &[&str] = &["ab", "cde", "fghi", "jklmn", "opqrst", "uvwxyzа"];
+
+    fn rules(wordlist: &'static [&'static str]) -> PasswordRules<'static> {
+        PasswordRules {
+            source: WordSource::Wordlist(wordlist),
+            num_words: 2,
+            separator: Separator::None,
+            append_numeral: true,
+            numeral_base: 10,
+            append_symbol: None,
+            symbol_count: 1,
+            symbol_position: SymbolPosition::End,
+            exact_length: None,
+            fixed_words: &[],
+            append_checksum: false,
+            sort_words: false,
+            fit_length: false,
+            word_weights: None,
+            case: WordCase::AsIs,
         }
+    }
+
+    /// Plan passwords of exactly `length`, and check that generated passwords
+    /// have that length
+    fn assert_exact_length(mut rules: PasswordRules<'static>, length: usize, encoding: Encoding) {
+        rules.exact_length = rules.plan_exact_length(length, encoding);
+        assert!(rules.exact_length.is_some(), "no plan for {}", length);
 
-        if let Some(symbol) = self.symbol {
-            symbol.fmt(f)?;
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for _ in 0..100 {
+            let password = rules.gen_password(&mut rng).to_string();
+            assert_eq!(
+                encoding.measure(password.as_str()),
+                length,
+                "{:?}",
+                password
+            );
         }
+    }
 
-        Ok(())
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-4,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn password_entropy_adds_up_the_components() {
+        let rules = PasswordRules {
+            separator: Separator::Random("-._"),
+            append_symbol: Some("!@#$"),
+            ..rules(WORDLIST)
+        };
+
+        let password = rules.gen_password(&mut StdRng::seed_from_u64(1));
+        let expected = 6f32.log2() + 5f32.log2() + 3f32.log2() + 10f32.log2() + 2.0;
+
+        assert_close(rules.password_entropy(&password), expected);
+    }
+
+    #[test]
+    fn password_entropy_counts_random_case() {
+        let rules = PasswordRules {
+            append_numeral: false,
+            case: WordCase::Random,
+            ..rules(WORDLIST)
+        };
+
+        let password = rules.gen_password(&mut StdRng::seed_from_u64(1));
+        let letters = password.words().flat_map(str::chars).count() as f32;
+
+        assert_close(
+            rules.password_entropy(&password),
+            6f32.log2() + 5f32.log2() + letters,
+        );
+    }
+
+    #[test]
+    fn password_entropy_uses_the_fitted_word_count() {
+        let rules = PasswordRules {
+            fit_length: true,
+            num_words: 6,
+            ..rules(WORDLIST)
+        };
+
+        let password = rules
+            .with_num_words(3)
+            .gen_password(&mut StdRng::seed_from_u64(1));
+        let expected = 6f32.log2() + 5f32.log2() + 4f32.log2() + 10f32.log2();
+
+        assert_close(rules.password_entropy(&password), expected);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "doesn't match its rules")]
+    fn password_entropy_checks_the_rules() {
+        let password = rules(WORDLIST).gen_password(&mut StdRng::seed_from_u64(1));
+
+        PasswordRules {
+            append_numeral: false,
+            ..rules(WORDLIST)
+        }
+        .password_entropy(&password);
+    }
+
+    #[test]
+    fn numeral_digits() {
+        let digits = |value, base| Numeral { value, base }.num_digits();
+
+        assert_eq!(digits(0, 10), 1);
+        assert_eq!(digits(9, 10), 1);
+        assert_eq!(digits(10, 10), 2);
+        assert_eq!(digits(200, 10), 3);
+        assert_eq!(digits(255, 2), Numeral::MAX_DIGITS);
+        assert_eq!(digits(35, 36), 1);
+        assert_eq!(digits(36, 36), 2);
+    }
+
+    #[test]
+    fn numerals_in_a_base_have_uniform_digits() {
+        for base in [2, 8, 10, 16, 36] {
+            assert_eq!(Numeral::uniform_digits(base), Some(1), "{}", base);
+        }
+    }
+
+    #[test]
+    fn exact_length_counts_the_numeral() {
+        for base in [2, 10, 36] {
+            let rules = PasswordRules {
+                numeral_base: base,
+                ..rules(WORDLIST)
+            };
+
+            assert_exact_length(rules, 8, Encoding::Bytes);
+        }
+
+        let rules = PasswordRules {
+            append_numeral: false,
+            ..rules(WORDLIST)
+        };
+
+        assert_exact_length(rules, 8, Encoding::Bytes);
+    }
+
+    #[test]
+    fn exact_length_counts_multi_byte_symbols() {
+        let rules = PasswordRules {
+            append_symbol: Some("€£¥"),
+            symbol_count: 2,
+            ..rules(WORDLIST)
+        };
+
+        // Each symbol is 1 character, but 2 or 3 bytes
+        assert_exact_length(rules.clone(), 10, Encoding::Chars);
+        assert_eq!(rules.plan_exact_length(10, Encoding::Bytes), None);
+
+        let rules = PasswordRules {
+            append_symbol: Some("€₤"),
+            ..rules
+        };
+
+        assert_exact_length(rules, 14, Encoding::Bytes);
+    }
+
+    #[test]
+    fn exact_length_counts_multi_byte_words() {
+        // The last word ends with a Cyrillic "а", which is 2 bytes
+        let rules = PasswordRules {
+            append_numeral: false,
+            num_words: 1,
+            ..rules(WORDLIST)
+        };
+
+        assert_exact_length(rules.clone(), 7, Encoding::Chars);
+        assert_exact_length(rules, 8, Encoding::Bytes);
+    }
+
+    #[test]
+    fn checksum_is_stable() {
+        // These are fixed, so that a checksum written down by an older
+        // version still checks out
+        assert_eq!(checksum(""), '0');
+        assert_eq!(checksum("a"), 'p');
+        assert_eq!(checksum("abc"), 'e');
+        assert_eq!(checksum("correct-horse-battery-staple"), '7');
+    }
+
+    #[test]
+    fn checksum_depends_on_order() {
+        assert_ne!(checksum("ab"), checksum("ba"));
+    }
+
+    /// A wordlist with all the words the same length, so that nothing about
+    /// the words themselves can bias the selection
+    const UNIFORM_WORDLIST: &[&str] = &[
+        "Apple", "Bacon", "Cedar", "Delta", "Eagle", "Fable", "Grape", "Hotel", "Igloo", "Joker",
+    ];
+
+    /// The critical value of the chi-squared distribution with 9 degrees of
+    /// freedom (one less than the size of UNIFORM_WORDLIST) at p = 0.001. A
+    /// uniform selection exceeds this only 0.1% of the time.
+    const CHI_SQUARED_CRITICAL: f64 = 27.877;
+
+    /// Generate the words of `count` passwords of `num_words` words each
+    fn gen_many_words(seed: u64, count: usize, num_words: usize) -> Vec<Vec<Cow<'static, str>>> {
+        let rules = PasswordRules {
+            num_words,
+            ..rules(UNIFORM_WORDLIST)
+        };
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        (0..count).map(|_| rules.gen_words(&mut rng)).collect()
+    }
+
+    #[test]
+    fn words_are_selected_uniformly() {
+        for &(seed, num_words) in &[(1, 1), (2, 3), (3, 6)] {
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+
+            for words in gen_many_words(seed, 5000, num_words) {
+                assert_eq!(words.len(), num_words);
+
+                for word in words {
+                    let word = UNIFORM_WORDLIST
+                        .iter()
+                        .find(|&&known| known == word)
+                        .expect("unknown word");
+                    *counts.entry(word).or_insert(0) += 1;
+                }
+            }
+
+            // Pearson's chi-squared statistic, against a uniform distribution
+            let expected = (5000 * num_words) as f64 / UNIFORM_WORDLIST.len() as f64;
+            let statistic: f64 = UNIFORM_WORDLIST
+                .iter()
+                .map(|word| counts.get(word).copied().unwrap_or(0) as f64)
+                .map(|observed| (observed - expected).powi(2) / expected)
+                .sum();
+
+            assert!(
+                statistic < CHI_SQUARED_CRITICAL,
+                "selection of {} words isn't uniform: chi-squared {:.2}, counts {:?}",
+                num_words,
+                statistic,
+                counts,
+            );
+        }
+    }
+
+    #[test]
+    fn words_are_not_repeated_within_a_password() {
+        for words in gen_many_words(4, 1000, 6) {
+            let mut sorted = words.clone();
+            sorted.sort();
+            sorted.dedup();
+
+            assert_eq!(sorted.len(), words.len(), "repeated word in {:?}", words);
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_words() {
+        assert_eq!(gen_many_words(5, 10, 4), gen_many_words(5, 10, 4));
     }
 }
@@ -0,0 +1,93 @@
+//! Spelling out passwords with the NATO phonetic alphabet, for --phonetic, so
+//! that they can be read aloud unambiguously (for instance, over the phone).
+
+use std::fmt::{self, Display, Formatter};
+
+/// The NATO phonetic alphabet, from A to Z
+const LETTERS: [&str; 26] = [
+    "Alfa", "Bravo", "Charlie", "Delta", "Echo", "Foxtrot", "Golf", "Hotel", "India", "Juliett",
+    "Kilo", "Lima", "Mike", "November", "Oscar", "Papa", "Quebec", "Romeo", "Sierra", "Tango",
+    "Uniform", "Victor", "Whiskey", "X-ray", "Yankee", "Zulu",
+];
+
+/// The names of the digits, from 0 to 9
+const DIGITS: [&str; 10] = [
+    "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine",
+];
+
+/// The names of the symbols in the default symbol set, and a few others
+const SYMBOLS: &[(char, &str)] = &[
+    (' ', "Space"),
+    ('!', "Exclamation"),
+    ('"', "Quote"),
+    ('#', "Hash"),
+    ('$', "Dollar"),
+    ('%', "Percent"),
+    ('&', "Ampersand"),
+    ('\'', "Apostrophe"),
+    ('(', "OpenParen"),
+    (')', "CloseParen"),
+    ('*', "Asterisk"),
+    ('+', "Plus"),
+    (',', "Comma"),
+    ('-', "Dash"),
+    ('.', "Period"),
+    ('/', "Slash"),
+    ('\\', "Backslash"),
+    (':', "Colon"),
+    (';', "Semicolon"),
+    ('<', "LessThan"),
+    ('=', "Equals"),
+    ('>', "GreaterThan"),
+    ('?', "Question"),
+    ('@', "At"),
+    ('[', "OpenBracket"),
+    (']', "CloseBracket"),
+    ('^', "Caret"),
+    ('_', "Underscore"),
+    ('`', "Backtick"),
+    ('{', "OpenBrace"),
+    ('|', "Pipe"),
+    ('}', "CloseBrace"),
+    ('~', "Tilde"),
+];
+
+/// Write the phonetic spelling of a single character. Uppercase letters are
+/// marked as capitals; characters without a spelling are written as
+/// themselves, in quotes.
+fn spell_char(f: &mut Formatter, c: char) -> fmt::Result {
+    if c.is_ascii_alphabetic() {
+        let word = LETTERS[(c.to_ascii_lowercase() as u8 - b'a') as usize];
+
+        match c.is_ascii_uppercase() {
+            true => write!(f, "Capital {}", word),
+            false => f.write_str(word),
+        }
+    } else if let Some(digit) = c.to_digit(10) {
+        f.write_str(DIGITS[digit as usize])
+    } else if let Some(&(_, name)) = SYMBOLS.iter().find(|&&(symbol, _)| symbol == c) {
+        f.write_str(name)
+    } else {
+        write!(f, "{:?}", c)
+    }
+}
+
+/// Spell out a password with the phonetic alphabet, one character at a time,
+/// separated by " - ", like "Capital Charlie - Oscar - Romeo"
+pub fn spell(password: &str) -> impl Display + '_ {
+    Spelled(password)
+}
+
+struct Spelled<'a>(&'a str);
+
+impl Display for Spelled<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.0.chars().enumerate().try_for_each(|(index, c)| {
+            if index > 0 {
+                f.write_str(" - ")?;
+            }
+
+            spell_char(f, c)
+        })
+    }
+}
@@ -0,0 +1,150 @@
+//! A simple profanity filter, for removing offensive words from a wordlist.
+//!
+//! A blocklist is a list of case-insensitive patterns. A pattern normally has
+//! to match an entire word, but it can start and/or end with a `*` wildcard to
+//! match the end, start, or any part of a word. For instance, `*shit*` blocks
+//! "Bullshit" and "Shitty", while `anal` blocks "Anal" but not "Analysis".
+
+/// The builtin blocklist. This is intentionally small; it's meant to catch the
+/// most common offensive words in the builtin wordlists, not to be exhaustive.
+const DEFAULT_BLOCKLIST: &[&str] = &[
+    "*fuck*",
+    "*shit*",
+    "*cunt*",
+    "*bitch*",
+    "*whore*",
+    "*slut*",
+    "*pussy*",
+    "*porn*",
+    "*dildo*",
+    "*nigger*",
+    "*nigga*",
+    "*faggot*",
+    "*wank*",
+    "*twat*",
+    "*bollock*",
+    "*cumshot*",
+    "*milf*",
+    "*blowjob*",
+    "*handjob*",
+    "*orgasm*",
+    "*erotic*",
+    "*fetish*",
+    "*boob*",
+    "*penis*",
+    "*vagina*",
+    "*asshole*",
+    "*gangbang*",
+    "*bukkake*",
+    "*shemale*",
+    "*hentai*",
+    "sex*",
+    "piss*",
+    "bastard*",
+    "retard*",
+    "xxx*",
+    "nude*",
+    "rapist*",
+    "rape",
+    "rapes",
+    "raped",
+    "ass",
+    "asses",
+    "arse",
+    "cock",
+    "cocks",
+    "dick",
+    "dicks",
+    "dickhead",
+    "tit",
+    "tits",
+    "titties",
+    "anal",
+    "anus",
+    "cum",
+    "cums",
+    "cumming",
+    "crap",
+    "damn",
+    "prick",
+    "horny",
+    "fag",
+    "fags",
+    "spic",
+    "kike",
+    "tranny",
+    "bdsm",
+    "threesome",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Pattern {
+    text: String,
+    wild_start: bool,
+    wild_end: bool,
+}
+
+impl Pattern {
+    fn new(pattern: &str) -> Self {
+        let wild_start = pattern.starts_with('*');
+        let pattern = pattern.strip_prefix('*').unwrap_or(pattern);
+        let wild_end = pattern.ends_with('*');
+        let pattern = pattern.strip_suffix('*').unwrap_or(pattern);
+
+        Pattern {
+            text: pattern.to_lowercase(),
+            wild_start,
+            wild_end,
+        }
+    }
+
+    /// Check if this pattern matches a word. The word must already be
+    /// lowercase.
+    fn matches(&self, word: &str) -> bool {
+        match (self.wild_start, self.wild_end) {
+            (false, false) => word == self.text,
+            (true, false) => word.ends_with(&self.text),
+            (false, true) => word.starts_with(&self.text),
+            (true, true) => word.contains(&self.text),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Blocklist {
+    patterns: Vec<Pattern>,
+}
+
+impl Blocklist {
+    /// Create a blocklist from the text of a blocklist file. It has the same
+    /// format as a wordlist: one pattern per line, ignoring blank lines and
+    /// lines starting with #.
+    pub fn from_text(text: &str) -> Self {
+        Blocklist {
+            patterns: text
+                .lines()
+                .map(|line| line.trim())
+                .filter(|line| !line.is_empty())
+                .filter(|line| !line.starts_with('#'))
+                .map(Pattern::new)
+                .collect(),
+        }
+    }
+
+    /// Check if a word is blocked by any pattern in this blocklist
+    pub fn blocks(&self, word: &str) -> bool {
+        let word = word.to_lowercase();
+        self.patterns.iter().any(|pattern| pattern.matches(&word))
+    }
+}
+
+impl Default for Blocklist {
+    fn default() -> Self {
+        Blocklist {
+            patterns: DEFAULT_BLOCKLIST
+                .iter()
+                .map(|pattern| Pattern::new(pattern))
+                .collect(),
+        }
+    }
+}
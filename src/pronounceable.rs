@@ -0,0 +1,44 @@
+//! Pronounceable pseudo-words, as an alternative to selecting words from a
+//! wordlist. Each pseudo-word is a series of consonant-vowel syllables, like
+//! "Tomuba" or "Ralethi".
+//!
+//! Every consonant is always followed by a vowel, so a given pseudo-word can
+//! only be produced by one sequence of syllables (for instance, "sha" can't be
+//! confused with "s" followed by "ha"). This ensures that the entropy of a
+//! pseudo-word is exactly the sum of the entropy of its syllables.
+
+use rand::seq::SliceRandom;
+use rand::{CryptoRng, Rng};
+
+const CONSONANTS: &[&str] = &[
+    "b", "c", "d", "f", "g", "h", "j", "k", "l", "m", "n", "p", "r", "s", "t", "v", "w", "z", "ch",
+    "sh", "th",
+];
+
+const VOWELS: &[&str] = &["a", "e", "i", "o", "u"];
+
+/// The number of distinct syllables that can be generated
+pub fn syllable_count() -> usize {
+    CONSONANTS.len() * VOWELS.len()
+}
+
+/// The entropy of a single randomly generated syllable
+pub fn syllable_entropy() -> f32 {
+    (syllable_count() as f32).log2()
+}
+
+/// Generate a title-cased pseudo-word with the given number of syllables
+pub fn gen_word<R: CryptoRng + Rng + ?Sized>(rng: &mut R, syllables: usize) -> String {
+    let mut word = String::with_capacity(syllables * 3);
+
+    for _ in 0..syllables {
+        word.push_str(CONSONANTS.choose(rng).expect("CONSONANTS is not empty"));
+        word.push_str(VOWELS.choose(rng).expect("VOWELS is not empty"));
+    }
+
+    if let Some(first) = word.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+
+    word
+}
@@ -0,0 +1,129 @@
+//! A self-test of the entropy model, for contributors (the hidden --self-test
+//! flag). For a few sets of rules using a tiny wordlist, it generates enough
+//! passwords to see every possible password many times, then compares the
+//! Shannon entropy of the observed distribution of passwords against the
+//! entropy that the rules claim. If the two disagree, either the generator
+//! isn't uniform or the entropy estimate is wrong.
+
+use std::collections::HashMap;
+
+use rand::{CryptoRng, Rng};
+
+use crate::password::{PasswordRules, Separator, SymbolPosition, WordCase, WordSource};
+
+const WORDS: &[&str] = &["Apple", "Bacon", "Cedar", "Delta", "Eagle", "Fable"];
+
+/// The number of passwords to generate for each set of rules
+const SAMPLE_SIZE: usize = 200_000;
+
+/// How far apart (in bits) the measured and claimed entropy may be. With at
+/// most a couple of thousand possible passwords, the sampling bias of the
+/// measurement is well under this.
+const TOLERANCE: f32 = 0.05;
+
+fn rules(num_words: usize) -> PasswordRules<'static> {
+    PasswordRules {
+        source: WordSource::Wordlist(WORDS),
+        num_words,
+        separator: Separator::None,
+        append_numeral: false,
+        numeral_base: 10,
+        append_symbol: None,
+        symbol_count: 1,
+        symbol_position: SymbolPosition::End,
+        exact_length: None,
+        fixed_words: &[],
+        append_checksum: false,
+        sort_words: false,
+        fit_length: false,
+        word_weights: None,
+        case: WordCase::AsIs,
+    }
+}
+
+/// The rules to test, with a description of each
+fn cases() -> Vec<(&'static str, PasswordRules<'static>)> {
+    vec![
+        ("2 words", rules(2)),
+        (
+            "3 words and a numeral",
+            PasswordRules {
+                append_numeral: true,
+                ..rules(3)
+            },
+        ),
+        (
+            "2 words with random separators",
+            PasswordRules {
+                separator: Separator::Random("-_."),
+                ..rules(2)
+            },
+        ),
+        (
+            "4 sorted words",
+            PasswordRules {
+                sort_words: true,
+                ..rules(4)
+            },
+        ),
+        (
+            "2 words, a base 4 numeral, and 2 symbols at each end",
+            PasswordRules {
+                append_numeral: true,
+                numeral_base: 4,
+                append_symbol: Some("!?"),
+                symbol_count: 2,
+                symbol_position: SymbolPosition::Both,
+                ..rules(2)
+            },
+        ),
+    ]
+}
+
+/// The entropy that the rules claim for each password
+fn claimed_entropy(rules: &PasswordRules) -> f32 {
+    rules.words_entropy()
+        + rules.separator_entropy()
+        + rules.numeral_entropy()
+        + rules.symbol_entropy()
+}
+
+/// The Shannon entropy of the passwords generated from the rules, measured
+/// from SAMPLE_SIZE samples
+fn measured_entropy<R: CryptoRng + Rng + ?Sized>(rules: &PasswordRules, rng: &mut R) -> f32 {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+
+    for _ in 0..SAMPLE_SIZE {
+        *counts
+            .entry(rules.gen_password(rng).to_string())
+            .or_insert(0) += 1;
+    }
+
+    let entropy: f64 = counts
+        .values()
+        .map(|&count| count as f64 / SAMPLE_SIZE as f64)
+        .map(|probability| -probability * probability.log2())
+        .sum();
+
+    entropy as f32
+}
+
+/// Run the self-test, reporting the result of each case to stderr. Returns
+/// true if they all passed.
+pub fn run<R: CryptoRng + Rng + ?Sized>(rng: &mut R) -> bool {
+    cases().iter().fold(true, |passed, (name, rules)| {
+        let claimed = claimed_entropy(rules);
+        let measured = measured_entropy(rules, rng);
+        let ok = (claimed - measured).abs() <= TOLERANCE;
+
+        eprintln!(
+            "{result}: {name}: claimed {claimed:.3} bits, measured {measured:.3} bits",
+            result = if ok { "pass" } else { "FAIL" },
+            name = name,
+            claimed = claimed,
+            measured = measured,
+        );
+
+        passed && ok
+    })
+}
@@ -0,0 +1,144 @@
+//! Compact password specs, for --spec. A spec like `4w+n+s` describes the
+//! shape of a password (4 words, a numeral, and a symbol) without reference
+//! to any particular wordlist, so it can be embedded in scripts and bound to
+//! a wordlist later.
+//!
+//! The grammar is:
+//!
+//! ```text
+//! spec  = words *("+" extra)
+//! words = count "w"
+//! extra = "n" / [count] "s"
+//! count = 1*DIGIT
+//! ```
+//!
+//! The word count may be 0, as with --word-count, but a symbol count must be
+//! at least 1. Each extra may appear at most once, in either order. There's
+//! no whitespace. A spec is displayed in its canonical form, with the numeral
+//! before the symbols and a symbol count of 1 left out, so that parsing a
+//! displayed spec always gives back the same spec.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::password::PasswordRules;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidSpec {
+    #[error("Password spec must start with a word count, like 4w")]
+    MissingWords,
+
+    #[error("Invalid count {count:?} in password spec; must be a number of at least {min}")]
+    InvalidCount { count: String, min: usize },
+
+    #[error("Unknown item {item:?} in password spec; must be n, s, or a count followed by s")]
+    UnknownItem { item: String },
+
+    #[error("Password spec has more than one +{item}")]
+    Duplicate { item: char },
+}
+
+/// The shape of a password: how many words it has, and whether it has a
+/// numeral and symbols. It doesn't borrow a wordlist or symbol set; see
+/// `Opt::apply_spec` for how it's bound to the rest of the options.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PasswordSpec {
+    pub words: u16,
+    pub numeral: bool,
+
+    /// The number of symbols, or 0 for none
+    pub symbols: usize,
+}
+
+/// Parse a count in a spec, which must be at least `min`. Signs aren't
+/// allowed.
+fn parse_count<T: FromStr + PartialOrd + From<u8>>(count: &str, min: u8) -> Result<T, InvalidSpec> {
+    count
+        .bytes()
+        .all(|b| b.is_ascii_digit())
+        .then(|| count.parse().ok())
+        .flatten()
+        .filter(|count| *count >= T::from(min))
+        .ok_or_else(|| InvalidSpec::InvalidCount {
+            count: count.to_string(),
+            min: min.into(),
+        })
+}
+
+impl FromStr for PasswordSpec {
+    type Err = InvalidSpec;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut items = s.split('+');
+
+        let words = items
+            .next()
+            .and_then(|words| words.strip_suffix('w'))
+            .ok_or(InvalidSpec::MissingWords)?;
+
+        let mut spec = PasswordSpec {
+            words: parse_count(words, 0)?,
+            numeral: false,
+            symbols: 0,
+        };
+
+        for item in items {
+            if item == "n" {
+                if spec.numeral {
+                    return Err(InvalidSpec::Duplicate { item: 'n' });
+                }
+
+                spec.numeral = true;
+            } else if let Some(count) = item.strip_suffix('s') {
+                if spec.symbols > 0 {
+                    return Err(InvalidSpec::Duplicate { item: 's' });
+                }
+
+                spec.symbols = match count {
+                    "" => 1,
+                    count => parse_count(count, 1)?,
+                };
+            } else {
+                return Err(InvalidSpec::UnknownItem {
+                    item: item.to_string(),
+                });
+            }
+        }
+
+        Ok(spec)
+    }
+}
+
+impl Display for PasswordSpec {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}w", self.words)?;
+
+        if self.numeral {
+            f.write_str("+n")?;
+        }
+
+        match self.symbols {
+            0 => Ok(()),
+            1 => f.write_str("+s"),
+            count => write!(f, "+{}s", count),
+        }
+    }
+}
+
+impl From<&PasswordRules<'_>> for PasswordSpec {
+    /// The spec for a set of rules. Anything the spec can't describe, like
+    /// the separator or where the symbols go, is left out. As with
+    /// --symbol-count, the symbol count is per end of the password.
+    fn from(rules: &PasswordRules) -> Self {
+        PasswordSpec {
+            words: rules.num_words as u16,
+            numeral: rules.append_numeral,
+            symbols: match rules.append_symbol {
+                Some(_) => rules.symbol_count,
+                None => 0,
+            },
+        }
+    }
+}
@@ -0,0 +1,208 @@
+//! Output templates, for --output-template. A template is a string with
+//! placeholders like `{words}` or `{entropy}`, which are filled in for each
+//! generated password. Literal braces are written as `{{` and `}}`.
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::password::{Component, Password};
+use crate::util::Encoding;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum InvalidTemplate {
+    #[error(
+        "Unknown placeholder {{{name}}} in output template; must be one of: {}",
+        Placeholder::NAMES.join(", ")
+    )]
+    UnknownPlaceholder { name: String },
+
+    #[error("Unclosed {{ in output template; use {{{{ for a literal brace")]
+    Unclosed,
+
+    #[error("Unmatched }} in output template; use }}}} for a literal brace")]
+    Unmatched,
+}
+
+/// A value that can be filled into a template
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    /// The whole password
+    Password,
+
+    /// The words of the password, with their separators
+    Words,
+
+    Numeral,
+    Symbol,
+    Checksum,
+
+    /// The estimated entropy of the password, in bits
+    Entropy,
+
+    /// The length of the password, in bytes or characters
+    Length,
+}
+
+impl Placeholder {
+    /// The names of each placeholder, in the same order as VALUES
+    pub const NAMES: &'static [&'static str] = &[
+        "password", "words", "numeral", "symbol", "checksum", "entropy", "length",
+    ];
+    const VALUES: &'static [Placeholder] = &[
+        Placeholder::Password,
+        Placeholder::Words,
+        Placeholder::Numeral,
+        Placeholder::Symbol,
+        Placeholder::Checksum,
+        Placeholder::Entropy,
+        Placeholder::Length,
+    ];
+}
+
+impl FromStr for Placeholder {
+    type Err = InvalidTemplate;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Placeholder::NAMES
+            .iter()
+            .zip(Placeholder::VALUES)
+            .find(|(name, _)| s.trim() == **name)
+            .map(|(_, &placeholder)| placeholder)
+            .ok_or_else(|| InvalidTemplate::UnknownPlaceholder {
+                name: s.to_string(),
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Placeholder(Placeholder),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl FromStr for Template {
+    type Err = InvalidTemplate;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(InvalidTemplate::Unclosed),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    parts.push(Part::Placeholder(name.parse()?));
+                }
+                '}' => return Err(InvalidTemplate::Unmatched),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+
+        Ok(Template { parts })
+    }
+}
+
+impl Template {
+    /// Check if the template uses a placeholder
+    pub fn uses(&self, placeholder: Placeholder) -> bool {
+        self.parts.contains(&Part::Placeholder(placeholder))
+    }
+
+    /// Fill in the template for a password. `entropy` is only used if the
+    /// template has an {entropy} placeholder.
+    pub fn render<'t>(
+        &'t self,
+        password: &'t Password,
+        entropy: Option<f32>,
+        encoding: Encoding,
+    ) -> impl Display + 't {
+        Rendered {
+            template: self,
+            password,
+            entropy,
+            encoding,
+        }
+    }
+}
+
+struct Rendered<'t, 'p> {
+    template: &'t Template,
+    password: &'t Password<'p>,
+    entropy: Option<f32>,
+    encoding: Encoding,
+}
+
+impl Rendered<'_, '_> {
+    /// Write just the components of the password that match the filter
+    fn write_components(
+        &self,
+        f: &mut Formatter,
+        filter: impl Fn(&Component) -> bool,
+    ) -> fmt::Result {
+        self.password
+            .components()
+            .filter(|component| filter(component))
+            .try_for_each(|component| component.fmt(f))
+    }
+}
+
+impl Display for Rendered<'_, '_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.template.parts.iter().try_for_each(|part| match part {
+            Part::Literal(text) => f.write_str(text),
+            Part::Placeholder(Placeholder::Password) => self.password.fmt(f),
+            Part::Placeholder(Placeholder::Words) => self.write_components(f, |component| {
+                matches!(component, Component::Word(_) | Component::Separator(_))
+            }),
+            Part::Placeholder(Placeholder::Numeral) => {
+                self.write_components(f, |component| matches!(component, Component::Numeral(_)))
+            }
+            Part::Placeholder(Placeholder::Symbol) => {
+                self.write_components(f, |component| matches!(component, Component::Symbol(_)))
+            }
+            Part::Placeholder(Placeholder::Checksum) => {
+                self.write_components(f, |component| matches!(component, Component::Checksum(_)))
+            }
+            Part::Placeholder(Placeholder::Entropy) => match self.entropy {
+                Some(entropy) => write!(f, "{:.2}", entropy),
+                None => f.write_str("?"),
+            },
+            Part::Placeholder(Placeholder::Length) => {
+                write!(f, "{}", self.encoding.measure(self.password))
+            }
+        })
+    }
+}
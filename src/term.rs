@@ -0,0 +1,52 @@
+//! Wrapping the verbose explanations on stderr to the width of the terminal,
+//! so that long lines don't wrap in the middle of words.
+//!
+//! This requires the `wrap` feature, and is only supported on unix. Without
+//! it, or if stderr isn't a terminal, messages are printed unwrapped.
+
+use std::borrow::Cow;
+
+/// The width of the terminal that stderr is connected to, if any
+#[cfg(all(unix, feature = "wrap"))]
+fn stderr_width() -> Option<usize> {
+    if !atty::is(atty::Stream::Stderr) {
+        return None;
+    }
+
+    // SAFETY: winsize is plain old data, so it can be zeroed, and TIOCGWINSZ
+    // only writes to the winsize it's given.
+    let size = unsafe {
+        let mut size: libc::winsize = std::mem::zeroed();
+        match libc::ioctl(libc::STDERR_FILENO, libc::TIOCGWINSZ, &mut size) {
+            0 => size,
+            _ => return None,
+        }
+    };
+
+    match size.ws_col {
+        0 => None,
+        width => Some(width.into()),
+    }
+}
+
+/// Wrap a message to the width of the terminal on stderr. In a very narrow
+/// terminal, words are broken up.
+#[cfg(all(unix, feature = "wrap"))]
+pub fn wrap(message: &str) -> Cow<'_, str> {
+    match stderr_width() {
+        Some(width) => Cow::Owned(textwrap::fill(message, width)),
+        None => Cow::Borrowed(message),
+    }
+}
+
+#[cfg(not(all(unix, feature = "wrap")))]
+pub fn wrap(message: &str) -> Cow<'_, str> {
+    Cow::Borrowed(message)
+}
+
+/// Like eprintln, but wrapped to the width of the terminal; see `wrap`
+macro_rules! eprintln_wrapped {
+    ($($arg:tt)*) => {
+        eprintln!("{}", $crate::term::wrap(&format!($($arg)*)))
+    };
+}
@@ -1,21 +1,121 @@
 use std::fmt::Display;
 
 use lazy_format::lazy_format;
+use thiserror::Error;
+
+/// Give a fieldless enum a name for each of its variants, and parse it from
+/// those names (case-insensitively). This adds `NAMES`, the valid names in
+/// the order they're listed in --help, and implements `FromStr` with a new
+/// error struct, which names the invalid input and lists the valid names:
+///
+/// ```ignore
+/// named_enum! {
+///     pub Encoding, InvalidEncoding("encoding") {
+///         "bytes" => Bytes,
+///         "chars" => Chars,
+///     }
+/// }
+/// ```
+macro_rules! named_enum {
+    (
+        $vis:vis $Enum:ident, $Error:ident($description:literal) {
+            $($name:literal => $Variant:ident,)*
+        }
+    ) => {
+        impl $Enum {
+            /// The valid names of each variant, in the order they're listed
+            /// in --help
+            $vis const NAMES: &'static [&'static str] = &[$($name,)*];
+        }
+
+        #[derive(Debug, Clone, Default, PartialEq, Eq, thiserror::Error)]
+        #[error(
+            "Invalid {} {input:?}; must be one of: {}",
+            $description,
+            $Enum::NAMES.join(", ")
+        )]
+        $vis struct $Error {
+            input: String,
+        }
+
+        impl std::str::FromStr for $Enum {
+            type Err = $Error;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                $(
+                    if s.eq_ignore_ascii_case($name) {
+                        return Ok($Enum::$Variant);
+                    }
+                )*
+
+                Err($Error {
+                    input: s.to_string(),
+                })
+            }
+        }
+    };
+}
 
 pub trait Len {
+    /// The length of this thing, in bytes
     fn len(&self) -> usize;
+
+    /// The length of this thing, in unicode scalar values
+    fn char_len(&self) -> usize;
 }
 
 impl Len for str {
     fn len(&self) -> usize {
         str::len(self)
     }
+
+    fn char_len(&self) -> usize {
+        self.chars().count()
+    }
 }
 
-impl<'a, T: Len + ?Sized> Len for &'a T {
+impl<T: Len + ?Sized> Len for &T {
     fn len(&self) -> usize {
         T::len(self)
     }
+
+    fn char_len(&self) -> usize {
+        T::char_len(self)
+    }
+}
+
+/// The unit in which lengths are measured
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Encoding {
+    /// Measure lengths in UTF-8 bytes
+    Bytes,
+
+    /// Measure lengths in unicode scalar values
+    Chars,
+}
+
+named_enum! {
+    pub Encoding, InvalidEncoding("encoding") {
+        "bytes" => Bytes,
+        "chars" => Chars,
+    }
+}
+
+impl Encoding {
+    pub fn measure<T: Len + ?Sized>(self, thing: &T) -> usize {
+        match self {
+            Encoding::Bytes => thing.len(),
+            Encoding::Chars => thing.char_len(),
+        }
+    }
+
+    /// The name of this unit, in a format satisfying "a length of 5 {}"
+    pub fn unit_name(self) -> &'static str {
+        match self {
+            Encoding::Bytes => "bytes",
+            Encoding::Chars => "characters",
+        }
+    }
 }
 
 // This struct encompasses an inclusive [min, max] range and is used for checking
@@ -26,13 +126,54 @@ pub struct Bounds {
     pub max: usize,
 }
 
-#[derive(Debug, Clone)]
+// InvalidBoundsError is an error indicating that a set of bounds couldn't be
+// calculated, because the min was greater than the max
+#[derive(Debug, Clone, Error)]
+#[error("minimum length {min} greater than maximum length {max}")]
+pub struct InvalidBoundsError {
+    pub min: usize,
+    pub max: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
 pub enum BoundsError {
+    #[error("greater than the maximum of {0}")]
     TooHigh(usize),
+
+    #[error("less than the minimum of {0}")]
     TooLow(usize),
 }
 
 impl Bounds {
+    /// Combine the user's requested min and max (either of which may be
+    /// missing) with defaults. A missing bound takes its default, unless
+    /// that would conflict with the bound the user did give, in which case
+    /// it's clamped to match it: `--max 3` with a default min of 4 gives
+    /// bounds of exactly 3.
+    pub fn clamp_default(
+        user_min: Option<usize>,
+        user_max: Option<usize>,
+        default_min: usize,
+        default_max: usize,
+    ) -> Result<Bounds, InvalidBoundsError> {
+        match (user_min, user_max) {
+            (None, None) => Ok(Bounds {
+                min: default_min,
+                max: default_max,
+            }),
+            (Some(min), None) => Ok(Bounds {
+                min,
+                max: default_max.max(min),
+            }),
+            (None, Some(max)) => Ok(Bounds {
+                min: default_min.min(max),
+                max,
+            }),
+            (Some(min), Some(max)) if min > max => Err(InvalidBoundsError { min, max }),
+            (Some(min), Some(max)) => Ok(Bounds { min, max }),
+        }
+    }
+
     pub fn check(&self, value: usize) -> Result<usize, BoundsError> {
         if value < self.min {
             Err(BoundsError::TooLow(self.min))
@@ -43,8 +184,24 @@ impl Bounds {
         }
     }
 
-    pub fn check_len<T: Len>(&self, thing: T) -> Result<T, BoundsError> {
-        self.check(thing.len()).map(move |_| thing)
+    pub fn check_len<T: Len>(&self, thing: T, encoding: Encoding) -> Result<T, BoundsError> {
+        self.check(encoding.measure(&thing)).map(move |_| thing)
+    }
+
+    /// Loosen these bounds by `step`: raise the max or, if there isn't one,
+    /// lower the min. Returns None if the bounds can't be loosened any further.
+    pub fn widen(&self, step: usize) -> Option<Bounds> {
+        match (self.min, self.max) {
+            (0, usize::MAX) => None,
+            (min, usize::MAX) => Some(Bounds {
+                min: min.saturating_sub(step),
+                max: usize::MAX,
+            }),
+            (min, max) => Some(Bounds {
+                min,
+                max: max.saturating_add(step),
+            }),
+        }
     }
 
     /// Write these bounds to a stream, in a format satisying "a length of {} bytes"
@@ -61,3 +218,83 @@ impl Bounds {
         })
     }
 }
+
+const SECONDS_PER_MINUTE: f64 = 60.0;
+const SECONDS_PER_HOUR: f64 = SECONDS_PER_MINUTE * 60.0;
+const SECONDS_PER_DAY: f64 = SECONDS_PER_HOUR * 24.0;
+const SECONDS_PER_YEAR: f64 = SECONDS_PER_DAY * 365.25;
+const SECONDS_PER_CENTURY: f64 = SECONDS_PER_YEAR * 100.0;
+
+// A common (and very rough) estimate for the heat death of the universe. Past
+// this point there's no meaningful difference between any two durations.
+const SECONDS_UNTIL_HEAT_DEATH: f64 = SECONDS_PER_YEAR * 1e100;
+
+/// Write a (potentially absurdly large) number of seconds in human units,
+/// in a format satisfying "it would take {}"
+pub fn display_duration(seconds: f64) -> impl Display {
+    lazy_format!(match (seconds) {
+        s if s.is_nan() || s >= SECONDS_UNTIL_HEAT_DEATH =>
+            "longer than the heat death of the universe",
+        s if s < 1.0 => "less than a second",
+        s if s < SECONDS_PER_MINUTE => "{s:.1} seconds",
+        s if s < SECONDS_PER_HOUR => ("{:.1} minutes", s / SECONDS_PER_MINUTE),
+        s if s < SECONDS_PER_DAY => ("{:.1} hours", s / SECONDS_PER_HOUR),
+        s if s < SECONDS_PER_YEAR => ("{:.1} days", s / SECONDS_PER_DAY),
+        s if s < SECONDS_PER_CENTURY => ("{:.1} years", s / SECONDS_PER_YEAR),
+        s if s < SECONDS_PER_CENTURY * 1e6 => ("{:.1} centuries", s / SECONDS_PER_CENTURY),
+        s => ("{:.2e} centuries", s / SECONDS_PER_CENTURY),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(min: usize, max: usize) -> Bounds {
+        Bounds { min, max }
+    }
+
+    #[test]
+    fn clamp_default_without_user_bounds() {
+        let clamped = Bounds::clamp_default(None, None, 4, 10).unwrap();
+        assert_eq!(clamped, bounds(4, 10));
+    }
+
+    #[test]
+    fn clamp_default_with_user_min() {
+        let clamped = Bounds::clamp_default(Some(6), None, 4, 10).unwrap();
+        assert_eq!(clamped, bounds(6, 10));
+
+        // The default max is raised to the user's min
+        let clamped = Bounds::clamp_default(Some(12), None, 4, 10).unwrap();
+        assert_eq!(clamped, bounds(12, 12));
+    }
+
+    #[test]
+    fn clamp_default_with_user_max() {
+        let clamped = Bounds::clamp_default(None, Some(8), 4, 10).unwrap();
+        assert_eq!(clamped, bounds(4, 8));
+
+        // The default min is lowered to the user's max
+        let clamped = Bounds::clamp_default(None, Some(3), 4, 10).unwrap();
+        assert_eq!(clamped, bounds(3, 3));
+    }
+
+    #[test]
+    fn clamp_default_with_both_user_bounds() {
+        // The defaults are ignored, even if the user's bounds are outside them
+        let clamped = Bounds::clamp_default(Some(2), Some(20), 4, 10).unwrap();
+        assert_eq!(clamped, bounds(2, 20));
+
+        let clamped = Bounds::clamp_default(Some(5), Some(5), 4, 10).unwrap();
+        assert_eq!(clamped, bounds(5, 5));
+    }
+
+    #[test]
+    fn clamp_default_with_inverted_user_bounds() {
+        let InvalidBoundsError { min, max } =
+            Bounds::clamp_default(Some(8), Some(6), 4, 10).unwrap_err();
+
+        assert_eq!((min, max), (8, 6));
+    }
+}
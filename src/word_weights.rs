@@ -0,0 +1,115 @@
+//! Non-uniform selection of words from a wordlist, for --prefer-short.
+//!
+//! When --max-length is tight, most uniformly chosen words are too long, and
+//! most passwords are rejected. Instead, each word is given a weight that
+//! falls off linearly with its length, so that the shortest words are chosen
+//! most often. Words are still distinct: a word that's already been chosen is
+//! simply drawn again.
+//!
+//! Because the words are no longer equally likely, the entropy we report is
+//! the min-entropy: the probability of the single most likely sequence of
+//! words, which is what an attacker who guesses the likeliest passwords first
+//! has to beat. It's always less than the entropy of uniform selection. The
+//! most likely sequence is also the shortest, so it's never rejected for its
+//! length, and the usual adjustment for rejected passwords still applies.
+
+use rand::{CryptoRng, Rng};
+
+use crate::util::Encoding;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordWeights {
+    /// cumulative[i] is the total weight of the words up to and including
+    /// the word at index i, so the last element is the total weight
+    cumulative: Vec<u64>,
+}
+
+impl WordWeights {
+    /// Weight each word in `wordlist` by how much shorter it is than the
+    /// longest word: the longest words have a weight of 1, words one unit
+    /// shorter have a weight of 2, and so on.
+    pub fn prefer_short(wordlist: &[&str], encoding: Encoding) -> Self {
+        let longest = wordlist
+            .iter()
+            .map(|word| encoding.measure(word))
+            .max()
+            .unwrap_or(0);
+
+        let cumulative = wordlist
+            .iter()
+            .scan(0, |total, word| {
+                *total += (longest + 1 - encoding.measure(word)) as u64;
+                Some(*total)
+            })
+            .collect();
+
+        WordWeights { cumulative }
+    }
+
+    /// The number of words that these weights are for
+    pub fn len(&self) -> usize {
+        self.cumulative.len()
+    }
+
+    fn total(&self) -> u64 {
+        self.cumulative.last().copied().unwrap_or(0)
+    }
+
+    fn weight(&self, index: usize) -> u64 {
+        match index {
+            0 => self.cumulative[0],
+            index => self.cumulative[index] - self.cumulative[index - 1],
+        }
+    }
+
+    /// Choose the index of a word, with probability proportional to its
+    /// weight
+    pub fn choose_index<R: CryptoRng + Rng + ?Sized>(&self, rng: &mut R) -> usize {
+        let choice = rng.gen_range(0..self.total());
+        self.cumulative.partition_point(|&total| total <= choice)
+    }
+
+    /// Choose the indexes of `count` distinct words. Panics if there are
+    /// fewer than `count` words.
+    pub fn choose_distinct<R: CryptoRng + Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+        count: usize,
+    ) -> Vec<usize> {
+        assert!(count <= self.len(), "count larger than wordlist size");
+
+        let mut indexes = Vec::with_capacity(count);
+
+        while indexes.len() < count {
+            let index = self.choose_index(rng);
+
+            if !indexes.contains(&index) {
+                indexes.push(index);
+            }
+        }
+
+        indexes
+    }
+
+    /// The min-entropy of `count` distinct words chosen with these weights.
+    /// The most likely sequence takes the heaviest remaining word each time;
+    /// each draw (after rejecting repeats) picks a word with probability
+    /// equal to its weight over the total weight of the words not yet
+    /// chosen.
+    pub fn entropy(&self, count: usize) -> f32 {
+        let mut weights: Vec<u64> = (0..self.len()).map(|index| self.weight(index)).collect();
+        weights.sort_unstable_by(|a, b| b.cmp(a));
+
+        let mut remaining = self.total();
+
+        weights
+            .into_iter()
+            .take(count)
+            .map(|weight| {
+                let probability = weight as f64 / remaining as f64;
+                remaining -= weight;
+                -probability.log2()
+            })
+            .sum::<f64>() as f32
+    }
+}
@@ -3,42 +3,308 @@
 //! String and generate a Vec<&str> for it. This reduces allocation pressure
 //! and improves memory locality.
 
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
 use std::io;
+use std::iter;
+use std::path::Path;
+use std::slice;
+use std::sync::OnceLock;
+
+use thiserror::Error;
+
+use crate::latin;
+
+/// Provenance information about a builtin wordlist, from the `# source:`,
+/// `# license:`, and `# locale:` lines in its header comments. See
+/// `wordlist_meta`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WordlistMeta {
+    pub source: Option<&'static str>,
+    pub license: Option<&'static str>,
+
+    /// The language the words are in, as a lowercase locale code like "en"
+    /// or "pt_br". Used by `wordlist_for_locale`.
+    pub locale: Option<&'static str>,
+}
+
+/// Find the builtin wordlist for a locale, like "de" or "en_US.UTF-8" (as in
+/// $LANG). The encoding is ignored, and if there's no wordlist for the exact
+/// region, the wordlist for the language is used.
+pub fn locale_wordlist(locale: &str) -> Option<&'static str> {
+    let locale = locale
+        .split('.')
+        .next()
+        .unwrap_or_default()
+        .to_lowercase()
+        .replace('-', "_");
+
+    wordlist_for_locale(&locale)
+        .or_else(|| {
+            let (language, _region) = locale.split_once('_')?;
+            wordlist_for_locale(language)
+        })
+        .filter(|name| !is_denied(name))
+}
 
 include!(concat!(env!("OUT_DIR"), "/wordlists_gen.rs"));
 
+/// A builtin wordlist, stored as a single string of newline-separated words,
+/// with the packed-wordlists feature. An array of `&str` needs a pointer and
+/// a length for every word (and, in a position-independent binary, a
+/// relocation for every pointer), which is several times the size of the
+/// words themselves; this needs one byte per word. The words are split out
+/// the first time the wordlist is used.
+#[cfg_attr(not(feature = "packed-wordlists"), allow(dead_code))]
+pub struct PackedWordlist {
+    text: &'static str,
+    words: OnceLock<Vec<&'static str>>,
+}
+
+#[cfg_attr(not(feature = "packed-wordlists"), allow(dead_code))]
+impl PackedWordlist {
+    pub const fn new(text: &'static str) -> Self {
+        PackedWordlist {
+            text,
+            words: OnceLock::new(),
+        }
+    }
+
+    pub fn words(&'static self) -> &'static [&'static str] {
+        self.words.get_or_init(|| self.text.lines().collect())
+    }
+}
+
+/// Builtin wordlists that can't be used in this build: a comma-separated
+/// list of names, from $MAKEPASS_DENY_WORDLISTS at compile time. This lets
+/// a curated distribution of makepass withhold some of the wordlists.
+const BUILD_DENIED_WORDLISTS: &str = match option_env!("MAKEPASS_DENY_WORDLISTS") {
+    Some(names) => names,
+    None => "",
+};
+
+/// Builtin wordlists denied at runtime, with --deny-wordlist
+static RUNTIME_DENIED_WORDLISTS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Deny these builtin wordlists for the rest of the process, in addition to
+/// any denied at compile time. Only the first call has any effect.
+pub fn deny_wordlists(names: Vec<String>) {
+    let _ = RUNTIME_DENIED_WORDLISTS.set(names);
+}
+
+/// Check if a builtin wordlist has been denied, either at compile time or
+/// with `deny_wordlists`. Denying a wordlist also denies its aliases, and
+/// denying an alias denies the wordlist it refers to.
+pub fn is_denied(name: &str) -> bool {
+    let resolve = |name: &str| wordlist_alias(name).unwrap_or(name).to_string();
+    let target = resolve(name);
+
+    BUILD_DENIED_WORDLISTS
+        .split(',')
+        .map(str::trim)
+        .filter(|denied| !denied.is_empty())
+        .chain(
+            RUNTIME_DENIED_WORDLISTS
+                .get()
+                .into_iter()
+                .flatten()
+                .map(String::as_str),
+        )
+        .any(|denied| resolve(denied) == target)
+}
+
+#[derive(Debug, Error)]
+pub enum WordlistFileError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error("wordlist input was not valid UTF-8 (at byte offset {offset})")]
+    InvalidUtf8 { offset: usize },
+
+    #[error(transparent)]
+    Syntax(#[from] toml::de::Error),
+}
+
 // TODO: Use rental here, instead of WordlistStoreage and Wordlist
 #[derive(Debug, Clone)]
 pub enum WordlistStorage {
     Static(&'static [&'static str]),
-    Runtime(String),
+
+    /// A wordlist loaded at runtime. Words are separated by the separator, or
+    /// are one per line if it's None.
+    Runtime {
+        text: String,
+        separator: Option<String>,
+    },
+
+    /// A wordlist divided into named categories of words, like "noun" or
+    /// "verb". Loaded from a TOML file, where each category is an array of
+    /// words. Each category is trimmed and deduplicated at load time. The
+    /// categories are kept in order of their names.
+    Categorized(BTreeMap<String, Vec<String>>),
 }
 
 impl WordlistStorage {
+    /// Load a builtin wordlist by name. Returns None if there's no such
+    /// wordlist, or if it's been denied; see `is_denied`.
     pub fn from_name(name: &str) -> Option<Self> {
+        if is_denied(name) {
+            return None;
+        }
+
         get_static_wordlist(name).map(WordlistStorage::Static)
     }
 
-    pub fn from_stream(mut stream: impl io::Read) -> io::Result<Self> {
-        let mut storage = String::new();
-        stream.read_to_string(&mut storage)?;
-        Ok(WordlistStorage::Runtime(storage))
+    /// Compose the Latin letters and combining marks in a runtime wordlist
+    /// into precomposed characters; see the `latin` module. Builtin wordlists
+    /// are already composed.
+    pub fn compose_latin(self) -> Self {
+        match self {
+            WordlistStorage::Static(list) => WordlistStorage::Static(list),
+            WordlistStorage::Runtime { text, separator } => WordlistStorage::Runtime {
+                text: latin::compose(&text).into_owned(),
+                separator,
+            },
+            WordlistStorage::Categorized(categories) => WordlistStorage::Categorized(
+                categories
+                    .into_iter()
+                    .map(|(name, words)| {
+                        let mut seen = HashSet::new();
+
+                        let words = words
+                            .iter()
+                            .map(|word| latin::compose(word).into_owned())
+                            .filter(|word| seen.insert(word.to_lowercase()))
+                            .collect();
+
+                        (name, words)
+                    })
+                    .collect(),
+            ),
+        }
     }
 
-    pub fn as_wordlist(&self) -> Wordlist {
+    /// Read a wordlist from a stream. The words are separated by
+    /// `separator`, or are one per line if it's None.
+    pub fn from_stream(
+        mut stream: impl io::Read,
+        separator: Option<&str>,
+    ) -> Result<Self, WordlistFileError> {
+        let mut bytes = Vec::new();
+        stream.read_to_end(&mut bytes)?;
+        let text = decode_utf8(bytes)?;
+
+        Ok(WordlistStorage::Runtime {
+            text,
+            separator: separator.map(String::from),
+        })
+    }
+
+    /// Load a wordlist from a file. If the file has a .toml extension, it's
+    /// loaded as a categorized wordlist; otherwise, it has the same format as a
+    /// builtin wordlist, or the words are separated by `separator`, if given.
+    pub fn from_file(path: &Path, separator: Option<&str>) -> Result<Self, WordlistFileError> {
+        let text = decode_utf8(fs::read(path)?)?;
+
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_categorized(&text),
+            _ => Ok(WordlistStorage::Runtime {
+                text,
+                separator: separator.map(String::from),
+            }),
+        }
+    }
+
+    pub fn from_categorized(text: &str) -> Result<Self, WordlistFileError> {
+        let categories: BTreeMap<String, Vec<String>> = toml::from_str(text)?;
+
+        Ok(WordlistStorage::Categorized(
+            categories
+                .into_iter()
+                .map(|(name, words)| {
+                    let mut seen = HashSet::new();
+
+                    let words = words
+                        .iter()
+                        .map(|word| word.trim())
+                        .filter(|word| !word.is_empty())
+                        .filter(|word| seen.insert(word.to_lowercase()))
+                        .map(|word| word.to_string())
+                        .collect();
+
+                    (name, words)
+                })
+                .collect(),
+        ))
+    }
+
+    /// Get the words in a named category. Returns None if this isn't a
+    /// categorized wordlist, or if there's no such category.
+    pub fn category(&self, name: &str) -> Option<&[String]> {
+        match self {
+            WordlistStorage::Categorized(categories) => {
+                categories.get(name).map(|words| words.as_slice())
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_wordlist(&self) -> Wordlist<'_> {
         match self {
             WordlistStorage::Static(list) => Wordlist::Static(list),
-            WordlistStorage::Runtime(blob) => Wordlist::Runtime(
-                blob.lines()
-                    .map(|line| line.trim())
-                    .filter(|line| !line.is_empty())
-                    .filter(|line| !line.starts_with('#'))
-                    .collect(),
-            ),
+            WordlistStorage::Runtime { text, separator } => {
+                // Runtime wordlists may contain duplicates, which would inflate
+                // the entropy estimate, so remove them (case-insensitively,
+                // keeping the first occurrence of each word).
+                let mut seen = HashSet::new();
+
+                let lines: Box<dyn Iterator<Item = &str>> = match separator {
+                    Some(separator) => Box::new(text.split(separator.as_str())),
+                    None => Box::new(text.lines()),
+                };
+
+                // Anything after a # is a comment, whether it's a whole line
+                // or follows a word (like "Word  # rank:3")
+                Wordlist::Runtime(
+                    lines
+                        .map(|line| strip_comment(line).trim())
+                        .filter(|line| !line.is_empty())
+                        .filter(|line| seen.insert(line.to_lowercase()))
+                        .collect(),
+                )
+            }
+            WordlistStorage::Categorized(categories) => {
+                let mut seen = HashSet::new();
+
+                Wordlist::Runtime(
+                    categories
+                        .iter()
+                        .flat_map(|(_, words)| words)
+                        .map(|word| word.as_str())
+                        .filter(|word| seen.insert(word.to_lowercase()))
+                        .collect(),
+                )
+            }
         }
     }
 }
 
+/// Decode wordlist input as UTF-8, reporting where the first invalid byte is
+/// if it isn't.
+fn decode_utf8(bytes: Vec<u8>) -> Result<String, WordlistFileError> {
+    String::from_utf8(bytes).map_err(|err| WordlistFileError::InvalidUtf8 {
+        offset: err.utf8_error().valid_up_to(),
+    })
+}
+
+/// Remove a trailing # comment from a line of a runtime wordlist
+fn strip_comment(line: &str) -> &str {
+    match line.split_once('#') {
+        Some((content, _)) => content,
+        None => line,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum Wordlist<'a> {
     Static(&'static [&'static str]),
@@ -53,7 +319,63 @@ impl<'a> Wordlist<'a> {
         }
     }
 
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &str> {
         self.as_slice().iter().copied()
     }
 }
+
+/// Iterate over the words in a wordlist, so that it can be used directly in a
+/// `for` loop, like `for word in &wordlist`.
+impl<'s, 'a> IntoIterator for &'s Wordlist<'a> {
+    type Item = &'a str;
+    type IntoIter = iter::Copied<slice::Iter<'s, &'a str>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.as_slice().iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wordlists_iterate_in_for_loops() {
+        let runtime = WordlistStorage::Runtime {
+            text: "apple\nbacon # a comment\nApple\ncedar\n".to_string(),
+            separator: None,
+        };
+
+        for wordlist in [
+            Wordlist::Static(&["apple", "bacon", "cedar"]),
+            runtime.as_wordlist(),
+        ] {
+            let mut words = Vec::new();
+
+            for word in &wordlist {
+                words.push(word);
+            }
+
+            assert_eq!(words, ["apple", "bacon", "cedar"]);
+            assert!(words.iter().copied().eq(wordlist.iter()));
+        }
+    }
+
+    #[test]
+    fn trailing_comments_are_stripped() {
+        let storage = WordlistStorage::Runtime {
+            text: "word # note\nbacon#rank:3\n  cedar\t# tab\n# comment\n#\n".to_string(),
+            separator: None,
+        };
+
+        assert_eq!(storage.as_wordlist().as_slice(), ["word", "bacon", "cedar"]);
+    }
+}
@@ -0,0 +1,54 @@
+//! Checks that --first-letter-acronym prints the first letter of each word,
+//! in the same case as the password.
+
+mod common;
+
+fn acronyms(args: &[&str]) -> Vec<(String, String)> {
+    let (passwords, stderr) = common::success(common::run(&common::seeded(&[
+        &[
+            "--num-passwords",
+            "10",
+            "--separator",
+            " ",
+            "--no-append-numeral",
+        ],
+        &["--first-letter-acronym"],
+        args,
+    ])));
+
+    let acronyms = stderr
+        .lines()
+        .filter_map(|line| line.strip_prefix("Acronym: "));
+
+    passwords
+        .lines()
+        .map(str::to_string)
+        .zip(acronyms.map(str::to_string))
+        .collect()
+}
+
+fn first_letters(password: &str) -> String {
+    password
+        .split(' ')
+        .filter_map(|word| word.chars().next())
+        .collect()
+}
+
+#[test]
+fn acronym_is_the_first_letters() {
+    let pairs = acronyms(&[]);
+    assert_eq!(pairs.len(), 10);
+
+    for (password, acronym) in pairs {
+        assert_eq!(acronym, first_letters(&password), "{:?}", password);
+        assert_eq!(acronym.chars().count(), 4, "{:?}", password);
+    }
+}
+
+#[test]
+fn acronym_follows_the_case() {
+    for (password, acronym) in acronyms(&["--all-lower"]) {
+        assert_eq!(acronym, first_letters(&password), "{:?}", password);
+        assert_eq!(acronym, acronym.to_lowercase(), "{:?}", password);
+    }
+}
@@ -0,0 +1,86 @@
+//! Checks that --show-alternatives prints other valid passwords to stderr,
+//! without changing the password on stdout, and that it stays within the
+//! --retries budget.
+
+mod common;
+
+const LABEL: &str = "Alternatives (";
+
+/// Run makepass with the given wordlist on stdin, and return stdout and
+/// stderr
+fn makepass(wordlist: &[&str], args: &[&str]) -> (String, String) {
+    let args = common::seeded(&[
+        &[
+            "--wordlist",
+            "stdin",
+            "--min-length",
+            "0",
+            "--no-append-numeral",
+        ],
+        &["--min-word", "1", "--word-count", "2", "--separator", "-"],
+        args,
+    ]);
+
+    common::success(common::run_with_stdin(&args, wordlist.join("\n")))
+}
+
+/// The alternatives listed after the label in stderr
+fn alternatives(stderr: &str) -> Vec<&str> {
+    stderr
+        .lines()
+        .skip_while(|line| !line.starts_with(LABEL))
+        .skip(1)
+        .map(str::trim)
+        .filter(|line| !line.starts_with('('))
+        .collect()
+}
+
+const WORDLIST: &[&str] = &["apple", "bacon", "cedar", "delta", "eagle", "fable"];
+
+#[test]
+fn alternatives_are_distinct_valid_passwords() {
+    let (stdout, stderr) = makepass(WORDLIST, &["--show-alternatives", "4"]);
+    let password = stdout.trim();
+    let alternatives = alternatives(&stderr);
+
+    assert_eq!(alternatives.len(), 4, "{}", stderr);
+
+    for (i, alternative) in alternatives.iter().enumerate() {
+        assert_ne!(*alternative, password);
+        assert!(!alternatives[..i].contains(alternative), "{}", stderr);
+
+        let words: Vec<&str> = alternative.split('-').collect();
+        assert_eq!(words.len(), 2, "{:?}", alternative);
+        assert!(words
+            .iter()
+            .all(|word| WORDLIST.contains(&word.to_lowercase().as_str())));
+    }
+}
+
+#[test]
+fn the_password_is_unchanged() {
+    let (with, _) = makepass(WORDLIST, &["--show-alternatives", "3"]);
+    let (without, stderr) = makepass(WORDLIST, &[]);
+
+    assert_eq!(with, without);
+    assert!(!stderr.contains(LABEL));
+}
+
+#[test]
+fn alternatives_stop_at_the_retry_limit() {
+    // There are only 3 sorted pairs of 3 words, so at most 2 alternatives
+    let (stdout, stderr) = makepass(
+        &["ab", "cd", "ef"],
+        &[
+            "--sort-words",
+            "--retries",
+            "200",
+            "--show-alternatives",
+            "5",
+        ],
+    );
+
+    assert_eq!(stdout.lines().count(), 1);
+    assert_eq!(alternatives(&stderr).len(), 2, "{}", stderr);
+    assert!(stderr.contains("only found 2 of 5"), "{}", stderr);
+}
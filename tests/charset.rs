@@ -0,0 +1,39 @@
+//! Checks that --charset generates strings of the requested length from the
+//! given characters, and rejects a length of 0.
+
+mod common;
+
+#[test]
+fn passwords_have_the_requested_length_and_characters() {
+    let stdout = common::makepass(&[
+        "--charset",
+        "abc",
+        "--char-length",
+        "12",
+        "--num-passwords",
+        "20",
+    ]);
+
+    for password in stdout.lines() {
+        assert_eq!(password.len(), 12, "{:?}", password);
+        assert!(
+            password.chars().all(|c| "abc".contains(c)),
+            "{:?}",
+            password
+        );
+    }
+}
+
+#[test]
+fn zero_length_is_an_error() {
+    for args in [&["--char-length", "0"], &["--max-length", "0"]] {
+        let output = common::run(&[&["--charset", "ab"][..], args].concat());
+
+        assert!(output.stdout.is_empty(), "{:?}", output);
+        assert!(
+            common::failure(output).contains("The --charset password would be empty"),
+            "{:?}",
+            args
+        );
+    }
+}
@@ -0,0 +1,46 @@
+//! Checks the layout of --print-wordlist with --columns.
+
+mod common;
+
+use common::makepass_with_words as print_wordlist;
+
+const WORDS: &[&str] = &["a", "bb", "ccc", "dddd", "e", "f", "g"];
+
+#[test]
+fn words_run_down_aligned_columns() {
+    assert_eq!(
+        print_wordlist(WORDS, &["--print-wordlist", "--columns", "3"]),
+        "a     dddd  g\n\
+         bb    e\n\
+         ccc   f\n"
+    );
+}
+
+#[test]
+fn filtered_wordlist_uses_columns() {
+    assert_eq!(
+        print_wordlist(
+            WORDS,
+            &[
+                "--print-filtered-wordlist",
+                "--min-word",
+                "2",
+                "--columns",
+                "2"
+            ]
+        ),
+        "bb    dddd\n\
+         ccc\n"
+    );
+}
+
+#[test]
+fn one_column_is_one_word_per_line() {
+    let expected = WORDS.join("\n") + "\n";
+
+    assert_eq!(print_wordlist(WORDS, &["--print-wordlist"]), expected);
+    assert_eq!(
+        print_wordlist(WORDS, &["--print-wordlist", "--columns", "1"]),
+        expected
+    );
+}
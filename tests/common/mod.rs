@@ -0,0 +1,140 @@
+//! Helpers shared by the integration tests, which all run the makepass binary.
+//! Each test file includes this with `mod common;`, and uses only some of it.
+#![allow(dead_code)]
+
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// The arguments that make makepass's output reproducible: a fixed seed, and
+/// a newline after every password, so that the output splits into lines
+pub const SEEDED: &[&str] = &["--seed", "1", "--newline", "always"];
+
+/// SEEDED, followed by each group of `args` in turn, for tests that need the
+/// same passwords on every run
+pub fn seeded<'a>(args: &[&[&'a str]]) -> Vec<&'a str> {
+    SEEDED
+        .iter()
+        .copied()
+        .chain(args.iter().flat_map(|args| args.iter().copied()))
+        .collect()
+}
+
+/// A command to run makepass with `args`, isolated from the environment of
+/// whoever runs the tests: XDG_CONFIG_HOME points at a directory without a
+/// makepass config file, and $MAKEPASS_WORDLIST and $LANG are unset, so the
+/// default wordlist is used unless the test picks another one.
+pub fn command(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_makepass"));
+
+    command
+        .env("XDG_CONFIG_HOME", env!("CARGO_TARGET_TMPDIR"))
+        .env_remove("MAKEPASS_WORDLIST")
+        .env_remove("LANG")
+        .args(args);
+
+    command
+}
+
+/// Run makepass with `args`, and return its output, whether or not it
+/// succeeded
+pub fn run(args: &[&str]) -> Output {
+    command(args)
+        .stdin(Stdio::null())
+        .output()
+        .expect("failed to run makepass")
+}
+
+/// Run makepass with `args`, with `stdin` as its standard input (for
+/// instance, a wordlist for `--wordlist stdin`), and return its output,
+/// whether or not it succeeded
+pub fn run_with_stdin(args: &[&str], stdin: impl AsRef<[u8]>) -> Output {
+    output_with_stdin(command(args), stdin)
+}
+
+/// Run a makepass `command` (from [`command`]) with `stdin` as its standard
+/// input, and return its output, whether or not it succeeded
+pub fn output_with_stdin(mut command: Command, stdin: impl AsRef<[u8]>) -> Output {
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("failed to run makepass");
+
+    // makepass may exit (for instance, with an error) without reading all of
+    // its input, so a broken pipe here isn't a failure; the output says what
+    // actually happened
+    let _ = child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(stdin.as_ref());
+
+    child.wait_with_output().expect("failed to run makepass")
+}
+
+/// Assert that makepass succeeded, and return its stdout and stderr
+pub fn success(output: Output) -> (String, String) {
+    assert!(output.status.success(), "makepass failed: {:?}", output);
+
+    (
+        String::from_utf8(output.stdout).expect("stdout is UTF-8"),
+        String::from_utf8(output.stderr).expect("stderr is UTF-8"),
+    )
+}
+
+/// Assert that makepass failed, and return its stderr
+pub fn failure(output: Output) -> String {
+    assert!(!output.status.success(), "makepass succeeded: {:?}", output);
+
+    String::from_utf8(output.stderr).expect("stderr is UTF-8")
+}
+
+/// Run makepass with `args`, assert that it succeeded, and return its stdout
+pub fn makepass(args: &[&str]) -> String {
+    success(run(args)).0
+}
+
+/// Run makepass with `args` and a wordlist of `words` on stdin, assert that
+/// it succeeded, and return its stdout
+pub fn makepass_with_words(words: &[&str], args: &[&str]) -> String {
+    let args = [&["--wordlist", "stdin"][..], args].concat();
+    success(run_with_stdin(&args, words.join("\n"))).0
+}
+
+/// The JSON text of the value of `key` in a JSON object printed by makepass:
+/// for instance, `"abc"`, `12.5`, `null`, or `["a","b"]`. This skips over
+/// strings, arrays, and objects, but doesn't otherwise check that the JSON
+/// is valid. The first occurrence of the key, at any depth, is used.
+pub fn json_value<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let pattern = format!("\"{}\":", key);
+    let start = json.find(&pattern)? + pattern.len();
+    let value = &json[start..];
+
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in value.char_indices() {
+        match (c, in_string) {
+            _ if escaped => escaped = false,
+            ('\\', true) => escaped = true,
+            ('"', _) => in_string = !in_string,
+            (_, true) => {}
+            (',' | ']' | '}', false) if depth == 0 => return Some(&value[..index]),
+            ('[' | '{', false) => depth += 1,
+            (']' | '}', false) => depth -= 1,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// The number that's the value of `key` in a JSON object; see `json_value`
+pub fn json_number(json: &str, key: &str) -> f64 {
+    json_value(json, key)
+        .unwrap_or_else(|| panic!("no {} in {}", key, json))
+        .parse()
+        .unwrap_or_else(|_| panic!("{} isn't a number in {}", key, json))
+}
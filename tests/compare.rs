@@ -0,0 +1,38 @@
+//! Checks the output of --compare for two small wordlist files.
+
+use std::fs;
+use std::path::PathBuf;
+mod common;
+
+fn wordlist_file(name: &str, words: &[&str]) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, words.join("\n")).expect("failed to write the wordlist");
+    path
+}
+
+#[test]
+fn compare_shows_unique_and_common_words() {
+    let first = wordlist_file("compare-first.txt", &["Apple", "banana", "cherry"]);
+    let second = wordlist_file("compare-second.txt", &["date", "apple", "CHERRY"]);
+
+    let (first, second) = (first.to_str().unwrap(), second.to_str().unwrap());
+    let output = common::makepass(&["--compare", first, second]);
+
+    assert_eq!(
+        output,
+        format!(
+            "--- {first} (3 words)\n\
+             +++ {second} (3 words)\n \
+             Apple\n\
+             -banana\n \
+             cherry\n\
+             +date\n\
+             1 only in {first}, 1 only in {second}, 2 in both\n",
+        )
+    );
+}
+
+#[test]
+fn compare_needs_two_wordlists() {
+    common::failure(common::run(&["--compare", "english"]));
+}
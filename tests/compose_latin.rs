@@ -0,0 +1,51 @@
+//! Checks for --compose-latin, which composes accented Latin letters in
+//! runtime wordlists.
+
+mod common;
+
+/// Run makepass with a wordlist on stdin, and return its stdout
+fn run_with_wordlist(wordlist: &str, args: &[&str]) -> String {
+    let args = [&["--wordlist", "stdin"][..], args].concat();
+    common::success(common::run_with_stdin(&args, wordlist)).0
+}
+
+/// "Café", written with a precomposed é and with a combining acute accent
+const COMPOSED: &str = "Caf\u{e9}";
+const DECOMPOSED: &str = "Cafe\u{301}";
+
+#[test]
+fn words_are_unchanged_by_default() {
+    let wordlist = format!("{}\n{}\n", COMPOSED, DECOMPOSED);
+    let output = run_with_wordlist(&wordlist, &["--print-wordlist"]);
+
+    assert_eq!(output, wordlist);
+}
+
+#[test]
+fn composes_and_deduplicates() {
+    let wordlist = format!("{}\n{}\n", COMPOSED, DECOMPOSED);
+    let output = run_with_wordlist(&wordlist, &["--print-wordlist", "--compose-latin"]);
+
+    assert_eq!(output, format!("{}\n", COMPOSED));
+}
+
+#[test]
+fn composes_stacked_accents() {
+    // "ế" is an e with a circumflex and an acute accent
+    let output = run_with_wordlist(
+        "Vie\u{302}\u{301}t\n",
+        &["--print-wordlist", "--compose-latin"],
+    );
+
+    assert_eq!(output, "Vi\u{1ebf}t\n");
+}
+
+#[test]
+fn other_scripts_are_unchanged() {
+    // A Cyrillic "й", written as "и" and a combining breve, which NFC would
+    // compose
+    let wordlist = "\u{438}\u{306}\u{43e}\u{434}\n";
+    let output = run_with_wordlist(wordlist, &["--print-wordlist", "--compose-latin"]);
+
+    assert_eq!(output, wordlist);
+}
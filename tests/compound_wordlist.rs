@@ -0,0 +1,82 @@
+//! Checks that words with hyphens and apostrophes, like "Well-known" and
+//! "O'clock", are kept in wordlists, and that their punctuation counts
+//! towards their length.
+
+use std::fs;
+
+mod common;
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/compound.list");
+
+/// The words in the fixture, without the header comments
+fn fixture_words() -> Vec<String> {
+    fs::read_to_string(FIXTURE)
+        .expect("failed to read the fixture")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn makepass(args: &[&str]) -> String {
+    common::makepass(&[&["--wordlist-file", FIXTURE][..], args].concat())
+}
+
+#[test]
+fn punctuated_words_are_kept() {
+    let words = fixture_words();
+
+    assert!(words.iter().any(|word| word.contains('-')));
+    assert!(words.iter().any(|word| word.contains('\'')));
+    assert_eq!(
+        makepass(&["--print-wordlist"]).lines().collect::<Vec<_>>(),
+        words
+    );
+}
+
+#[test]
+fn punctuation_counts_towards_the_word_length() {
+    let expected: Vec<String> = fixture_words()
+        .into_iter()
+        .filter(|word| word.len() == 9)
+        .collect();
+
+    // Like "Long-term" and "Well-done"
+    assert!(!expected.is_empty());
+    assert!(expected.iter().all(|word| word.contains(['-', '\''])));
+
+    let filtered = makepass(&[
+        "--print-filtered-wordlist",
+        "--min-word",
+        "9",
+        "--max-word",
+        "9",
+    ]);
+    assert_eq!(filtered.lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn passwords_use_punctuated_words() {
+    let words = fixture_words();
+    let passwords = makepass(&common::seeded(&[&[
+        "--num-passwords",
+        "20",
+        "--min-word",
+        "1",
+        "--max-word",
+        "20",
+        "--min-length",
+        "0",
+        "--separator",
+        " ",
+        "--no-append-numeral",
+    ]]));
+
+    for password in passwords.lines() {
+        for word in password.split(' ') {
+            assert!(words.iter().any(|known| known == word), "{:?}", password);
+        }
+    }
+
+    assert!(passwords.contains(['-', '\'']), "{}", passwords);
+}
@@ -0,0 +1,89 @@
+//! Checks --format csv: the header row, the columns of each row, and that
+//! passwords with commas or double quotes are escaped as in RFC 4180.
+
+mod common;
+
+fn csv(args: &[&str]) -> String {
+    common::makepass(&common::seeded(&[
+        &["--min-length", "0", "--format", "csv"],
+        args,
+    ]))
+}
+
+/// Split a CSV row into its fields, undoing any quoting
+fn fields(row: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = row.chars().peekable();
+    let mut quoted = false;
+
+    while let Some(c) = chars.next() {
+        match (c, quoted) {
+            ('"', false) => quoted = true,
+            ('"', true) if chars.peek() == Some(&'"') => {
+                chars.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            ('"', true) => quoted = false,
+            (',', false) => fields.push(String::new()),
+            (c, _) => fields.last_mut().unwrap().push(c),
+        }
+    }
+
+    fields
+}
+
+#[test]
+fn rows_have_each_column() {
+    let output = csv(&["--num-passwords", "5", "--word-count", "3"]);
+    let mut rows = output.lines();
+
+    assert_eq!(
+        rows.next(),
+        Some("password,entropy,length_bytes,word_count")
+    );
+
+    let rows: Vec<Vec<String>> = rows.map(fields).collect();
+    assert_eq!(rows.len(), 5);
+
+    for row in rows {
+        assert_eq!(row.len(), 4, "{:?}", row);
+
+        let entropy: f64 = row[1].parse().expect("entropy is a number");
+        assert!(entropy > 0.0, "{:?}", row);
+
+        assert_eq!(row[2].parse(), Ok(row[0].len()), "{:?}", row);
+        assert_eq!(row[3], "3", "{:?}", row);
+    }
+}
+
+#[test]
+fn commas_and_quotes_are_escaped() {
+    for symbol in [",", "\""] {
+        let output = csv(&[
+            "--num-passwords",
+            "5",
+            "--symbol-set",
+            symbol,
+            "--symbol-count",
+            "2",
+        ]);
+
+        for row in output.lines().skip(1) {
+            let password = fields(row).remove(0);
+            let escaped = password.replace('"', "\"\"");
+
+            assert!(password.ends_with(&symbol.repeat(2)), "{:?}", row);
+            assert!(row.starts_with(&format!("\"{}\",", escaped)), "{:?}", row);
+            assert_eq!(fields(row).len(), 4, "{:?}", row);
+        }
+    }
+}
+
+#[test]
+fn plain_passwords_are_not_quoted() {
+    let output = csv(&["--num-passwords", "5", "--no-append-symbol"]);
+
+    for row in output.lines().skip(1) {
+        assert!(!row.contains('"'), "{:?}", row);
+    }
+}
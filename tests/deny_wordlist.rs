@@ -0,0 +1,61 @@
+//! Checks that a wordlist denied with --deny-wordlist can't be selected, and
+//! isn't listed.
+
+mod common;
+
+use common::run as makepass;
+
+/// A wordlist file, which can't be denied
+const WORDLIST_FILE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/compound.list");
+
+#[test]
+fn denied_wordlist_cant_be_selected() {
+    for args in [
+        &["--wordlist", "english"][..],
+        &["--wordlist-info", "english"],
+        &["--theme-wordlist", "english"],
+        &["--compare", WORDLIST_FILE, "english"],
+    ] {
+        let stderr = common::failure(makepass(
+            &[&["--deny-wordlist", "english"][..], args].concat(),
+        ));
+
+        assert!(stderr.contains("denied"), "{:?}: {}", args, stderr);
+    }
+}
+
+#[test]
+fn denying_an_alias_denies_its_target() {
+    common::failure(makepass(&[
+        "--deny-wordlist",
+        "default",
+        "--wordlist",
+        "english",
+    ]));
+    common::failure(makepass(&["--deny-wordlist", "english"]));
+}
+
+#[test]
+fn wordlist_files_are_still_available() {
+    common::success(makepass(&[
+        "--deny-wordlist",
+        "english",
+        "--wordlist-file",
+        WORDLIST_FILE,
+        "--min-length",
+        "0",
+    ]));
+}
+
+#[test]
+fn denied_wordlists_arent_listed() {
+    let (listed, _) = common::success(makepass(&["--list-wordlists"]));
+    assert!(listed.lines().any(|line| line == "english"), "{}", listed);
+
+    let (listed, _) = common::success(makepass(&[
+        "--list-wordlists",
+        "--deny-wordlist",
+        "english",
+    ]));
+    assert!(!listed.contains("english"), "{}", listed);
+}
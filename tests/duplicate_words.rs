@@ -0,0 +1,27 @@
+//! Checks that repeated words in a runtime wordlist are removed, ignoring
+//! case and keeping the first occurrence of each, so that they don't inflate
+//! the entropy estimate.
+
+mod common;
+
+const WORDLIST: &[&str] = &["Apple", "bacon", "APPLE", "cedar", "Bacon", "apple"];
+
+#[test]
+fn duplicates_are_removed_in_order() {
+    let words = common::makepass_with_words(WORDLIST, &["--print-wordlist"]);
+
+    assert_eq!(words, "Apple\nbacon\ncedar\n");
+}
+
+#[test]
+fn duplicates_dont_add_entropy() {
+    let explain = |words: &[&str]| {
+        let report = common::makepass_with_words(
+            words,
+            &["--explain", "--min-length", "0", "--word-count", "2"],
+        );
+        common::json_number(&report, "words")
+    };
+
+    assert_eq!(explain(WORDLIST), explain(&["Apple", "bacon", "cedar"]));
+}
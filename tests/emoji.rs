@@ -0,0 +1,90 @@
+//! Checks that symbol sets with emoji, including emoji made of several code
+//! points, are split into whole graphemes: each symbol in a password is one
+//! complete emoji from the set, and each emoji counts once for entropy.
+#![cfg(feature = "graphemes")]
+
+mod common;
+
+/// Emoji with 1, 2, 2, and 5 code points: a plain emoji, one with a skin tone
+/// modifier, a flag, and a family joined with zero-width joiners
+const EMOJI: &[&str] = &["🔑", "👍🏽", "🇺🇸", "👨‍👩‍👧"];
+
+fn makepass(args: &[&str]) -> String {
+    common::makepass(&common::seeded(&[&["--min-length", "0"], args]))
+}
+
+#[test]
+fn symbols_are_whole_emoji() {
+    let symbol_set = EMOJI.concat();
+    let output = makepass(&[
+        "--num-passwords",
+        "200",
+        "--no-append-numeral",
+        "--separator",
+        " ",
+        "--symbol-set",
+        &symbol_set,
+        "--symbol-count",
+        "2",
+    ]);
+
+    let mut seen = Vec::new();
+
+    for password in output.lines() {
+        let (_, mut symbols) = password
+            .rsplit_once(' ')
+            .unwrap_or_else(|| panic!("no separator in {:?}", password));
+
+        // The last word runs into the symbols; strip it off
+        symbols = symbols.trim_start_matches(|c: char| c.is_ascii_alphabetic());
+
+        for _ in 0..2 {
+            let emoji = EMOJI
+                .iter()
+                .find(|emoji| symbols.starts_with(*emoji))
+                .unwrap_or_else(|| panic!("broken emoji in {:?}", password));
+
+            symbols = &symbols[emoji.len()..];
+
+            if !seen.contains(emoji) {
+                seen.push(emoji);
+            }
+        }
+
+        assert_eq!(symbols, "", "{:?}", password);
+    }
+
+    assert_eq!(seen.len(), EMOJI.len(), "not every emoji was used");
+}
+
+#[test]
+fn each_emoji_is_one_symbol() {
+    let output = makepass(&[
+        "--explain",
+        "--sample-size",
+        "100",
+        "--symbol-set",
+        &EMOJI.concat(),
+    ]);
+
+    let entropy = common::json_number(&output, "symbol");
+
+    assert!((entropy - 2.0).abs() < 1e-6, "{}", entropy);
+}
+
+#[test]
+fn emoji_are_measured_in_bytes() {
+    let output = makepass(&[
+        "--num-passwords",
+        "50",
+        "--symbol-set",
+        &EMOJI.concat(),
+        "--output-template",
+        "{length} {password}",
+    ]);
+
+    for line in output.lines() {
+        let (length, password) = line.split_once(' ').expect("length and password");
+        assert_eq!(length.parse(), Ok(password.len()), "{:?}", password);
+    }
+}
@@ -0,0 +1,26 @@
+//! Checks that an empty wordlist is a clean error, rather than a panic or a
+//! password with no entropy.
+
+mod common;
+
+#[test]
+fn empty_stdin_is_an_error() {
+    for wordlist in ["", "\n\n", "   \n\t\n"] {
+        let output = common::run_with_stdin(&["--wordlist", "stdin"], wordlist);
+
+        assert!(output.stdout.is_empty(), "{:?}", output);
+        assert!(
+            common::failure(output).contains("The wordlist is empty"),
+            "{:?}",
+            wordlist
+        );
+    }
+}
+
+#[test]
+fn comment_only_stdin_is_an_error() {
+    let wordlist = "# A wordlist with no words\n\n# source: nowhere\n";
+    let stderr = common::failure(common::run_with_stdin(&["--wordlist", "stdin"], wordlist));
+
+    assert!(stderr.contains("The wordlist is empty"), "{}", stderr);
+}
@@ -0,0 +1,82 @@
+//! Checks that --encoding chooses whether the word and password lengths of an
+//! accented wordlist are measured in bytes or in characters.
+
+mod common;
+
+/// Each word's length in characters, then in UTF-8 bytes
+const WORDLIST: &[&str] = &[
+    "Café",   // 4, 5
+    "Naïve",  // 5, 6
+    "Crème",  // 5, 6
+    "Éclair", // 6, 7
+    "Olive",  // 5, 5
+];
+
+fn words_of_length(length: &str, args: &[&str]) -> Vec<String> {
+    let args = [
+        &[
+            "--print-filtered-wordlist",
+            "--min-word",
+            length,
+            "--max-word",
+            length,
+        ][..],
+        args,
+    ]
+    .concat();
+
+    common::makepass_with_words(WORDLIST, &args)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn word_lengths_are_in_bytes_by_default() {
+    assert_eq!(words_of_length("5", &[]), ["Café", "Olive"]);
+    assert_eq!(
+        words_of_length("5", &["--encoding", "bytes"]),
+        ["Café", "Olive"]
+    );
+    assert_eq!(words_of_length("6", &[]), ["Naïve", "Crème"]);
+}
+
+#[test]
+fn word_lengths_can_be_in_chars() {
+    assert_eq!(
+        words_of_length("5", &["--encoding", "chars"]),
+        ["Naïve", "Crème", "Olive"]
+    );
+    assert_eq!(words_of_length("6", &["--encoding", "chars"]), ["Éclair"]);
+}
+
+#[test]
+fn password_lengths_follow_the_encoding() {
+    let args = |encoding| {
+        common::seeded(&[&[
+            "--num-passwords",
+            "20",
+            "--word-count",
+            "2",
+            "--no-append-numeral",
+            "--min-word",
+            "1",
+            "--min-length",
+            "10",
+            "--max-length",
+            "10",
+            "--retries",
+            "10000",
+            "--encoding",
+            encoding,
+        ]])
+    };
+
+    for password in common::makepass_with_words(WORDLIST, &args("chars")).lines() {
+        assert_eq!(password.chars().count(), 10, "{:?}", password);
+    }
+
+    for password in common::makepass_with_words(WORDLIST, &args("bytes")).lines() {
+        assert_eq!(password.len(), 10, "{:?}", password);
+    }
+}
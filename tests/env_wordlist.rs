@@ -0,0 +1,80 @@
+//! Checks that $MAKEPASS_WORDLIST selects the wordlist when --wordlist isn't
+//! given, and that --wordlist and --wordlist-file take precedence over it.
+
+use std::fs;
+use std::path::PathBuf;
+
+mod common;
+
+const STDIN_WORDS: &str = "apple\nbacon\ncedar\n";
+
+/// The first few words printed by --print-wordlist, with `MAKEPASS_WORDLIST`
+/// set to `env` and STDIN_WORDS on stdin
+fn first_words(env: &str, args: &[&str]) -> Vec<String> {
+    let mut command = common::command(&[&["--print-wordlist"][..], args].concat());
+    command.env("MAKEPASS_WORDLIST", env);
+
+    let (stdout, _) = common::success(common::output_with_stdin(command, STDIN_WORDS));
+
+    stdout.lines().take(3).map(str::to_string).collect()
+}
+
+fn english_words() -> Vec<String> {
+    common::makepass(&["--print-wordlist", "--wordlist", "english"])
+        .lines()
+        .take(3)
+        .map(str::to_string)
+        .collect()
+}
+
+#[test]
+fn env_selects_the_wordlist() {
+    assert_eq!(first_words("stdin", &[]), ["apple", "bacon", "cedar"]);
+    assert_eq!(first_words("-", &[]), ["apple", "bacon", "cedar"]);
+}
+
+#[test]
+fn empty_env_is_ignored() {
+    assert_eq!(first_words("", &[]), english_words());
+}
+
+#[test]
+fn wordlist_flag_takes_precedence() {
+    assert_eq!(
+        first_words("stdin", &["--wordlist", "english"]),
+        english_words()
+    );
+    assert_eq!(
+        first_words("english", &["--wordlist", "stdin"]),
+        ["apple", "bacon", "cedar"]
+    );
+}
+
+#[test]
+fn wordlist_file_takes_precedence() {
+    let fixture = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/compound.list");
+
+    assert_eq!(
+        first_words("stdin", &["--wordlist-file", fixture]),
+        ["Well-known", "Long-term", "Self-esteem"]
+    );
+}
+
+#[test]
+fn wordlist_flag_takes_precedence_over_invalid_env() {
+    assert_eq!(
+        first_words("nonexistent", &["--wordlist", "stdin"]),
+        ["apple", "bacon", "cedar"]
+    );
+}
+
+#[test]
+fn env_takes_precedence_over_config() {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("env_wordlist.toml");
+    fs::write(&path, "wordlist = \"english\"\n").expect("failed to write the config file");
+
+    assert_eq!(
+        first_words("stdin", &["--config", path.to_str().unwrap()]),
+        ["apple", "bacon", "cedar"]
+    );
+}
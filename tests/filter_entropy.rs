@@ -0,0 +1,49 @@
+//! Checks that passwords rejected by the filters (not just the length
+//! constraints) count towards the sampling adjustment, so that the estimated
+//! entropy goes down as the filters get stricter.
+
+mod common;
+
+/// The sampling adjustment and total entropy reported by --explain
+fn explain(filters: &[&str]) -> (f64, f64) {
+    let report = common::makepass(&common::seeded(&[
+        &["--explain", "--sample-size", "20000", "--min-length", "0"],
+        filters,
+    ]));
+
+    (
+        common::json_number(&report, "sampling_adjustment"),
+        common::json_number(&report, "total"),
+    )
+}
+
+#[test]
+fn no_filters_no_adjustment() {
+    assert_eq!(explain(&[]).0, 0.0);
+}
+
+#[test]
+fn adjustment_grows_as_filters_tighten() {
+    let filters: [&[&str]; 4] = [
+        &[],
+        &["--avoid-sequences"],
+        &["--avoid-sequences", "--min-distinct-word-lengths", "3"],
+        &["--avoid-sequences", "--min-distinct-word-lengths", "4"],
+    ];
+
+    let reports: Vec<(f64, f64)> = filters.iter().map(|filters| explain(filters)).collect();
+
+    for (looser, tighter) in reports.iter().zip(&reports[1..]) {
+        assert!(tighter.0 < looser.0, "{:?}", reports);
+        assert!(tighter.1 < looser.1, "{:?}", reports);
+    }
+}
+
+#[test]
+fn substring_filter_counts_too() {
+    let (loose, _) = explain(&["--require-substring", "e"]);
+    let (tight, _) = explain(&["--require-substring", "er"]);
+
+    assert!(loose < 0.0, "{}", loose);
+    assert!(tight < loose, "{} >= {}", tight, loose);
+}
@@ -0,0 +1,76 @@
+//! Checks that --fit-length passwords fit the maximum length, and that each
+//! one's entropy comes from its own number of words.
+
+mod common;
+
+const WORDLIST: &[&str] = &["ab", "cde", "fghi", "jklmn", "opqrst"];
+
+fn fitted_passwords(args: &[&str]) -> Vec<String> {
+    let args = common::seeded(&[
+        &["--fit-length", "--max-length", "12", "--min-length", "0"],
+        &[
+            "--min-word",
+            "1",
+            "--no-prefer-short",
+            "--no-append-numeral",
+        ],
+        &["--num-passwords", "30", "--json-lines"],
+        args,
+    ]);
+
+    common::makepass_with_words(WORDLIST, &args)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+/// The number of words in a --json-lines password
+fn word_count(json: &str) -> usize {
+    match common::json_value(json, "words").expect("no words") {
+        "[]" => 0,
+        words => words.matches(',').count() + 1,
+    }
+}
+
+#[test]
+fn passwords_fit_the_maximum_length() {
+    for json in fitted_passwords(&[]) {
+        assert!(common::json_number(&json, "length") <= 12.0, "{}", json);
+        assert!(word_count(&json) >= 2, "{}", json);
+    }
+}
+
+#[test]
+fn entropy_depends_on_each_passwords_word_count() {
+    let passwords = fitted_passwords(&[]);
+
+    for json in &passwords {
+        // Words are chosen without repetition from the 5 in the wordlist
+        let expected: f64 = (0..word_count(json))
+            .map(|i| ((WORDLIST.len() - i) as f64).log2())
+            .sum();
+
+        assert!(
+            (common::json_number(json, "entropy") - expected).abs() < 1e-3,
+            "{}",
+            json
+        );
+    }
+
+    let counts: Vec<usize> = passwords.iter().map(|json| word_count(json)).collect();
+    assert!(counts.contains(&2) && counts.contains(&3), "{:?}", counts);
+}
+
+#[test]
+fn separators_and_checksum_count_towards_the_length() {
+    for json in fitted_passwords(&["--separator", "--", "--checksum"]) {
+        let password = common::json_value(&json, "password").unwrap();
+
+        // The JSON value includes the quotes around the password
+        assert!(password.len() - 2 <= 12, "{}", json);
+        assert_eq!(
+            common::json_number(&json, "length") as usize,
+            password.len() - 2
+        );
+    }
+}
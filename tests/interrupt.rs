@@ -0,0 +1,58 @@
+//! Checks that Ctrl-C stops makepass promptly, with its interrupted exit
+//! status, even partway through a long sample or a long search.
+#![cfg(all(unix, feature = "interrupt"))]
+
+use std::process::{Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+mod common;
+
+/// Start makepass, send it SIGINT once it's had time to install its handler,
+/// and return its exit code, or panic if it doesn't exit
+fn interrupt(args: &[&str]) -> Option<i32> {
+    let mut child = common::command(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .expect("failed to run makepass");
+
+    sleep(Duration::from_millis(500));
+
+    let kill = Command::new("kill")
+        .args(["-INT", &child.id().to_string()])
+        .status()
+        .expect("failed to run kill");
+    assert!(kill.success());
+
+    let start = Instant::now();
+
+    while start.elapsed() < Duration::from_secs(10) {
+        if let Some(status) = child.try_wait().expect("failed to wait for makepass") {
+            return status.code();
+        }
+
+        sleep(Duration::from_millis(50));
+    }
+
+    child.kill().expect("failed to kill makepass");
+    child.wait().expect("failed to wait for makepass");
+    panic!("makepass ignored SIGINT with {:?}", args);
+}
+
+#[test]
+fn interrupts_a_long_entropy_sample() {
+    assert_eq!(interrupt(&["-e", "--sample-size", "2000000000"]), Some(130));
+}
+
+#[test]
+fn interrupts_a_long_search() {
+    let args = ["--retries", "2000000000", "--min-length", "200"];
+    assert_eq!(interrupt(&args), Some(130));
+}
+
+#[test]
+fn interrupts_unlimited_retries() {
+    let args = ["--retries", "0", "--min-length", "200"];
+    assert_eq!(interrupt(&args), Some(130));
+}
@@ -0,0 +1,37 @@
+//! Checks that a wordlist that isn't valid UTF-8 is an error that says where
+//! the first invalid byte is.
+
+mod common;
+
+fn utf8_error(stdin: &[u8]) -> String {
+    let output = common::run_with_stdin(&["--wordlist", "stdin"], stdin);
+
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    common::failure(output)
+}
+
+#[test]
+fn invalid_byte_offset() {
+    let stderr = utf8_error(b"abc\ndef\xffgh\n");
+
+    assert!(
+        stderr.contains("wordlist input was not valid UTF-8 (at byte offset 7)"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn invalid_first_byte() {
+    let stderr = utf8_error(b"\xfeapple\nbacon\n");
+
+    assert!(stderr.contains("(at byte offset 0)"), "{}", stderr);
+}
+
+#[test]
+fn truncated_character_offset() {
+    // "é" is 0xc3 0xa9; the offset is the start of the truncated character
+    let stderr = utf8_error(b"caf\xc3\xa9\nna\xc3\nolive\n");
+
+    assert!(stderr.contains("(at byte offset 8)"), "{}", stderr);
+}
@@ -0,0 +1,97 @@
+//! Checks that --json-lines prints one complete JSON object per line, with the
+//! fields of each password, and with special characters escaped.
+
+mod common;
+
+use common::json_value;
+
+/// Run makepass with --json-lines, using the given wordlist, and return its
+/// output lines
+fn json_lines(wordlist: &[&str], args: &[&str]) -> Vec<String> {
+    common::makepass_with_words(
+        wordlist,
+        &common::seeded(&[
+            &["--min-word", "1", "--min-length", "0", "--json-lines"],
+            args,
+        ]),
+    )
+    .lines()
+    .map(str::to_string)
+    .collect()
+}
+
+/// The JSON text of the value of `key`, which must be present
+fn field<'a>(line: &'a str, key: &str) -> &'a str {
+    json_value(line, key).unwrap_or_else(|| panic!("no {} in {}", key, line))
+}
+
+/// The JSON text of each string in an array of strings, such as
+/// `["a","b\"c"]`. The strings in these tests don't contain `","`.
+fn strings(array: &str) -> Vec<&str> {
+    array
+        .strip_prefix("[\"")
+        .and_then(|array| array.strip_suffix("\"]"))
+        .unwrap_or_else(|| panic!("not an array of strings: {}", array))
+        .split("\",\"")
+        .collect()
+}
+
+#[test]
+fn each_line_is_a_json_object() {
+    let lines = json_lines(
+        &["Apple", "Bacon", "Cedar", "Delta"],
+        &["--num-passwords", "20", "--append-symbol", "--checksum"],
+    );
+
+    assert_eq!(lines.len(), 20);
+
+    for line in &lines {
+        assert!(line.starts_with('{') && line.ends_with('}'), "{}", line);
+
+        let password = field(line, "password");
+        assert!(
+            password.starts_with('"') && password.ends_with('"'),
+            "{}",
+            line
+        );
+
+        let words = strings(field(line, "words"));
+        assert_eq!(words.len(), 4, "{}", line);
+
+        for word in words {
+            assert!(password.contains(word), "{}", line);
+        }
+
+        for key in ["numeral", "symbols", "checksum"] {
+            assert!(field(line, key).starts_with('"'), "{}: {}", key, line);
+        }
+
+        for key in ["length", "entropy"] {
+            common::json_number(line, key);
+        }
+    }
+}
+
+#[test]
+fn special_characters_are_escaped() {
+    let lines = json_lines(
+        &["quote\"d", "for/ward", "plain"],
+        &[
+            "--num-passwords",
+            "5",
+            "--word-count",
+            "3",
+            "--no-append-numeral",
+        ],
+    );
+
+    assert_eq!(lines.len(), 5);
+
+    for line in &lines {
+        let mut words = strings(field(line, "words"));
+        words.sort_unstable();
+
+        assert_eq!(words, ["for/ward", "plain", "quote\\\"d"], "{}", line);
+        assert_eq!(field(line, "numeral"), "null", "{}", line);
+    }
+}
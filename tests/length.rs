@@ -0,0 +1,87 @@
+//! Checks that the length makepass measures for a password (as reported by the
+//! {length} template placeholder) matches the length of what's printed, for
+//! numerals in different bases and for multi-byte symbols.
+
+mod common;
+
+/// Generate passwords with the given extra arguments, and return each
+/// reported length along with the password itself
+fn measured(args: &[&str]) -> Vec<(usize, String)> {
+    let args = common::seeded(&[
+        &[
+            "--num-passwords",
+            "100",
+            "--word-count",
+            "2",
+            "--min-length",
+            "0",
+        ],
+        &["--output-template", "{length} {password}"],
+        args,
+    ]);
+
+    common::makepass(&args)
+        .lines()
+        .map(|line| {
+            let (length, password) = line.split_once(' ').expect("length and password");
+            (
+                length.parse().expect("length is a number"),
+                password.to_string(),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn single_digit_numerals_are_one_byte() {
+    for base in ["2", "10", "36"] {
+        let passwords = measured(&["--append-numeral", "--numeral-base", base]);
+        assert_eq!(passwords.len(), 100);
+
+        for (length, password) in passwords {
+            assert_eq!(length, password.len(), "{:?} in base {}", password, base);
+        }
+    }
+}
+
+#[test]
+fn multi_byte_symbols_are_measured_in_bytes() {
+    let passwords = measured(&["--symbol-set", "€£¥"]);
+    assert_eq!(passwords.len(), 100);
+
+    for (length, password) in passwords {
+        assert_eq!(length, password.len(), "{:?}", password);
+    }
+}
+
+#[test]
+fn multi_byte_symbols_are_one_char() {
+    let passwords = measured(&["--symbol-set", "€£¥", "--encoding", "chars"]);
+
+    for (length, password) in passwords {
+        assert_eq!(length, password.chars().count(), "{:?}", password);
+        assert!(length < password.len(), "{:?}", password);
+    }
+}
+
+#[test]
+fn length_bounds_apply_to_the_whole_rendered_password() {
+    let passwords = measured(&[
+        "--separator",
+        "--",
+        "--symbol-set",
+        "€!",
+        "--symbol-position",
+        "both",
+        "--checksum",
+        "--max-length",
+        "24",
+    ]);
+    assert_eq!(passwords.len(), 100);
+
+    for (length, password) in passwords {
+        assert_eq!(length, password.len(), "{:?}", password);
+        assert!(length <= 24, "{:?}", password);
+        assert!(password.contains("--"), "{:?}", password);
+    }
+}
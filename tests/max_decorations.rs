@@ -0,0 +1,36 @@
+//! Checks that --max-decorations caps the numeral digits and symbols in a
+//! password.
+
+use std::process::Output;
+
+mod common;
+
+fn makepass(args: &[&str]) -> Output {
+    common::run(&common::seeded(&[
+        &["--sample-size", "1000", "--retries", "1000"],
+        &["--symbol-set", "!", "--symbol-count", "2"],
+        args,
+    ]))
+}
+
+#[test]
+fn decorations_within_the_cap_are_accepted() {
+    let (password, _) = common::success(makepass(&["--max-decorations", "3"]));
+    assert!(password.trim_end().ends_with("!!"), "{:?}", password);
+}
+
+#[test]
+fn decorations_over_the_cap_are_rejected() {
+    common::failure(makepass(&["--max-decorations", "2"]));
+
+    // Without the numeral, 2 symbols are within the cap
+    common::success(makepass(&["--max-decorations", "2", "--no-append-numeral"]));
+}
+
+#[test]
+fn symbols_at_both_ends_count_towards_the_cap() {
+    let args = ["--symbol-position", "both", "--no-append-numeral"];
+
+    common::failure(makepass(&[&args[..], &["--max-decorations", "3"]].concat()));
+    common::success(makepass(&[&args[..], &["--max-decorations", "4"]].concat()));
+}
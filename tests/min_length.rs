@@ -0,0 +1,44 @@
+//! Checks that the default minimum password length is applied when there's no
+//! --min-length.
+
+mod common;
+
+/// Generate passwords with the given extra arguments, and return their
+/// lengths in bytes
+fn lengths(args: &[&str]) -> Vec<usize> {
+    let args = common::seeded(&[
+        &[
+            "--num-passwords",
+            "200",
+            "--word-count",
+            "3",
+            "--no-append-numeral",
+        ],
+        args,
+    ]);
+
+    common::makepass(&args).lines().map(str::len).collect()
+}
+
+#[test]
+fn default_min_length_is_24() {
+    let lengths = lengths(&[]);
+
+    assert_eq!(lengths.len(), 200);
+    assert!(lengths.iter().all(|&length| length >= 24), "{:?}", lengths);
+}
+
+#[test]
+fn default_min_length_can_be_overridden() {
+    let lengths = lengths(&["--default-min-length", "18"]);
+
+    assert!(lengths.iter().all(|&length| length >= 18), "{:?}", lengths);
+    assert!(lengths.iter().any(|&length| length < 24), "{:?}", lengths);
+}
+
+#[test]
+fn max_length_lowers_the_default_min_length() {
+    let lengths = lengths(&["--max-length", "16"]);
+
+    assert!(lengths.iter().all(|&length| length == 16), "{:?}", lengths);
+}
@@ -0,0 +1,60 @@
+//! Checks that --output-dir writes each password to its own file, readable
+//! only by its owner where that's possible.
+
+use std::fs;
+use std::path::PathBuf;
+
+mod common;
+
+/// An empty directory for this test's password files
+fn empty_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).expect("failed to create the output directory");
+    dir
+}
+
+#[test]
+fn each_password_gets_a_file() {
+    let dir = empty_dir("output_dir_files");
+    let passwords = common::makepass(&[
+        "--seed",
+        "1",
+        "--num-passwords",
+        "3",
+        "--output-dir",
+        dir.to_str().unwrap(),
+    ]);
+
+    assert_eq!(passwords, "");
+
+    for name in ["password-001.txt", "password-002.txt", "password-003.txt"] {
+        let password = fs::read_to_string(dir.join(name)).expect("missing password file");
+        assert!(!password.trim().is_empty(), "{} is empty", name);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(dir.join(name)).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600, "{}", name);
+        }
+    }
+}
+
+#[test]
+fn allow_unrestricted_files_requires_output_dir() {
+    common::failure(common::run(&["--allow-unrestricted-files"]));
+}
+
+#[cfg(not(unix))]
+#[test]
+fn unrestricted_files_need_opting_in() {
+    let dir = empty_dir("output_dir_unrestricted");
+    let dir = dir.to_str().unwrap();
+
+    let stderr = common::failure(common::run(&["--output-dir", dir]));
+    assert!(stderr.contains("--allow-unrestricted-files"), "{}", stderr);
+
+    common::makepass(&["--output-dir", dir, "--allow-unrestricted-files"]);
+}
@@ -0,0 +1,52 @@
+//! Checks the errors for invalid newline behaviors and wordlist selections,
+//! which name the invalid input and list the valid values. On the command
+//! line, clap checks these values itself, so they're tested through the
+//! config file and $MAKEPASS_WORDLIST.
+
+use std::fs;
+use std::path::PathBuf;
+
+mod common;
+
+/// Run makepass with a config file containing `contents`, and return its
+/// error
+fn config_error(name: &str, contents: &str) -> String {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    fs::write(&path, contents).expect("failed to write the config file");
+
+    common::failure(common::run(&["--config", path.to_str().unwrap()]))
+}
+
+#[test]
+fn invalid_newline_behavior() {
+    let stderr = config_error("invalid_newline.toml", "newline = \"alwys\"\n");
+
+    assert!(stderr.contains("line 1"), "{}", stderr);
+    assert!(
+        stderr.contains("Invalid newline behavior \"alwys\"; must be one of: never, always, auto"),
+        "{}",
+        stderr
+    );
+}
+
+#[test]
+fn invalid_wordlist_selection() {
+    let expected =
+        "Invalid wordlist selection \"\"; must be one of: default, en_us, english, stdin, -";
+
+    let stderr = config_error("invalid_wordlist.toml", "wordlist = \"\"\n");
+    assert!(stderr.contains("line 1"), "{}", stderr);
+    assert!(stderr.contains(expected), "{}", stderr);
+
+    let output = common::command(&[])
+        .env("MAKEPASS_WORDLIST", "  ")
+        .output()
+        .expect("failed to run makepass");
+    let stderr = common::failure(output);
+
+    assert!(
+        stderr.contains(&format!("Invalid $MAKEPASS_WORDLIST: {}", expected)),
+        "{}",
+        stderr
+    );
+}
@@ -0,0 +1,47 @@
+//! Checks that --prefer-short makes more passwords fit a tight --max-length,
+//! that it reduces the word entropy to pay for it, and when it's enabled by
+//! default.
+
+mod common;
+
+/// Run makepass --explain, and return the "words" entropy and the
+/// "success_size" from the report
+fn explain(args: &[&str]) -> (f64, f64) {
+    let report = common::makepass(&common::seeded(&[
+        &["--min-length", "0", "--no-append-numeral"],
+        &["--explain", "--sample-size", "20000"],
+        args,
+    ]));
+
+    (
+        common::json_number(&report, "words"),
+        common::json_number(&report, "success_size"),
+    )
+}
+
+#[test]
+fn prefer_short_trades_entropy_for_acceptance() {
+    let (uniform_entropy, uniform_accepted) = explain(&["--max-length", "20", "--no-prefer-short"]);
+    let (short_entropy, short_accepted) = explain(&["--max-length", "20", "--prefer-short"]);
+
+    assert!(short_entropy < uniform_entropy);
+    assert!(short_entropy > uniform_entropy - 8.0);
+    assert!(short_accepted > uniform_accepted * 2.0);
+}
+
+#[test]
+fn tight_max_length_prefers_short_by_default() {
+    assert_eq!(
+        explain(&["--max-length", "20"]).0,
+        explain(&["--max-length", "20", "--prefer-short"]).0,
+    );
+}
+
+#[test]
+fn loose_max_length_is_uniform_by_default() {
+    assert_eq!(
+        explain(&["--max-length", "60"]).0,
+        explain(&["--max-length", "60", "--no-prefer-short"]).0,
+    );
+    assert_eq!(explain(&[]).0, explain(&["--no-prefer-short"]).0);
+}
@@ -0,0 +1,33 @@
+//! Checks the entropy reported for --random-case.
+
+mod common;
+
+#[test]
+fn each_letter_adds_one_bit() {
+    // Every word has 5 letters, and the hyphen doesn't count
+    let (stdout, stderr) = common::success(common::run_with_stdin(
+        &[
+            &[
+                "--wordlist",
+                "stdin",
+                "--word-count",
+                "2",
+                "--min-length",
+                "0",
+            ][..],
+            &["--no-append-numeral", "--random-case", "--verbose"],
+            common::SEEDED,
+        ]
+        .concat(),
+        "Apple\nBacon\nCedar\nDelta\nWell-done\n",
+    ));
+
+    let letters = stdout.chars().filter(|c| c.is_alphabetic()).count();
+    let expected = format!(
+        "The case of each of the {letters} letters in the words was randomized, for an \
+         additional {letters}.00 bits of entropy.",
+        letters = letters,
+    );
+
+    assert!(stderr.contains(&expected), "{}", stderr);
+}
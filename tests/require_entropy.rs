@@ -0,0 +1,40 @@
+//! Checks that --require-entropy fails, without printing a password, when the
+//! adjusted entropy estimate is below the threshold.
+
+use std::process::Output;
+
+mod common;
+
+fn makepass(args: &[&str]) -> Output {
+    common::run(&common::seeded(&[
+        &["--sample-size", "5000", "--min-length", "30"],
+        args,
+    ]))
+}
+
+/// Get a number from the --explain report
+fn explained(key: &str) -> f32 {
+    let (report, _) = common::success(makepass(&["--explain"]));
+    common::json_number(&report, key) as f32
+}
+
+#[test]
+fn entropy_above_the_threshold_passes() {
+    let required = explained("total") - 1.0;
+    let (stdout, _) = common::success(makepass(&["--require-entropy", &required.to_string()]));
+
+    assert!(!stdout.is_empty());
+}
+
+#[test]
+fn sampling_adjustment_counts_against_the_threshold() {
+    let adjustment = explained("sampling_adjustment");
+    assert!(adjustment < -0.5, "{}", adjustment);
+
+    // Above the adjusted estimate, but below the unadjusted one
+    let required = explained("total") - adjustment / 2.0;
+    let output = makepass(&["--require-entropy", &required.to_string()]);
+
+    assert!(output.stdout.is_empty(), "{:?}", output);
+    assert!(common::failure(output).contains("--require-entropy"));
+}
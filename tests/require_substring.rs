@@ -0,0 +1,64 @@
+//! Checks that --require-substring only accepts passwords containing the
+//! fragment, and warns when almost no passwords do.
+
+use std::process::Output;
+
+mod common;
+
+fn makepass(args: &[&str]) -> Output {
+    common::run(&common::seeded(&[&["--sample-size", "2000"], args]))
+}
+
+#[test]
+fn passwords_contain_the_substring() {
+    let (stdout, _) = common::success(makepass(&[
+        "--require-substring",
+        "er",
+        "--num-passwords",
+        "20",
+    ]));
+    assert_eq!(stdout.lines().count(), 20);
+
+    for password in stdout.lines() {
+        assert!(password.contains("er"), "{:?}", password);
+    }
+}
+
+#[test]
+fn substring_reduces_the_entropy() {
+    let entropy = |args: &[&str]| -> f64 {
+        let (report, _) = common::success(makepass(&[&["--explain"][..], args].concat()));
+        common::json_number(&report, "total")
+    };
+
+    let plain = entropy(&[]);
+    let required = entropy(&["--require-substring", "er"]);
+
+    assert!(required < plain, "{} >= {}", required, plain);
+}
+
+#[test]
+fn rare_substring_warns() {
+    let stderr = common::failure(makepass(&[
+        "--require-substring",
+        "qqqq",
+        "--retries",
+        "10",
+    ]));
+    assert!(stderr.contains("Warning: only 0 of 2000"), "{}", stderr);
+    assert!(stderr.contains("\"qqqq\""), "{}", stderr);
+}
+
+#[test]
+fn warning_is_not_a_log_message() {
+    let stderr = common::failure(makepass(&[
+        "--require-substring",
+        "qqqq",
+        "--retries",
+        "10",
+        "--log-level",
+        "off",
+    ]));
+
+    assert!(stderr.contains("Warning: only 0 of 2000"), "{}", stderr);
+}
@@ -0,0 +1,65 @@
+//! Checks that --seed reproduces passwords, including from the seed printed by
+//! --show-seed, and that a seeded run gives exactly the expected passwords.
+
+mod common;
+
+/// Run makepass, and return its stdout and stderr
+fn makepass(args: &[&str]) -> (String, String) {
+    common::success(common::run(
+        &[&["--num-passwords", "5", "--newline", "always"][..], args].concat(),
+    ))
+}
+
+#[test]
+fn shown_seed_reproduces_passwords() {
+    let (first, stderr) = makepass(&["--show-seed"]);
+
+    let seed = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("Seed: "))
+        .expect("--show-seed prints the seed");
+
+    assert_eq!(seed.len(), 64, "seed {:?} isn't 256 bits", seed);
+
+    let (second, _) = makepass(&["--seed", seed]);
+    assert_eq!(first, second);
+}
+
+#[test]
+fn different_seeds_give_different_passwords() {
+    assert_ne!(makepass(&["--seed", "1"]).0, makepass(&["--seed", "2"]).0);
+}
+
+// These expected outputs only change if the way passwords are generated from
+// the RNG changes, which also changes the passwords for any saved seed. The
+// chacha20 feature swaps the RNG, so it has its own set.
+
+#[cfg(not(feature = "chacha20"))]
+const FIXED_PASSWORDS: &str = "ember-cedar-bacon7\ndelta-apple-cedar0\nfable-cedar-bacon3\n";
+#[cfg(feature = "chacha20")]
+const FIXED_PASSWORDS: &str = "delta-cedar-fable1\nbacon-apple-delta6\ndelta-bacon-cedar9\n";
+
+#[cfg(not(feature = "chacha20"))]
+const FIXED_BUILTIN_PASSWORDS: &str = "EpochConsumeAndrewsPlates5\nVolumeKangarooRoughlySaving3\n";
+#[cfg(feature = "chacha20")]
+const FIXED_BUILTIN_PASSWORDS: &str =
+    "HistoricFairlySurvivalWriters4\nAppointFacialNordicImpacted4\n";
+
+#[test]
+fn seeded_run_gives_fixed_passwords() {
+    let args = common::seeded(&[
+        &["--num-passwords", "3", "--word-count", "3"],
+        &["--min-length", "0", "--separator", "-"],
+    ]);
+    let words = ["apple", "bacon", "cedar", "delta", "ember", "fable"];
+
+    assert_eq!(common::makepass_with_words(&words, &args), FIXED_PASSWORDS);
+}
+
+#[test]
+fn seeded_run_with_builtin_wordlist_gives_fixed_passwords() {
+    assert_eq!(
+        common::makepass(&common::seeded(&[&["--num-passwords", "2"]])),
+        FIXED_BUILTIN_PASSWORDS
+    );
+}
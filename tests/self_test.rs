@@ -0,0 +1,16 @@
+//! Runs makepass's own entropy self-test (the hidden --self-test flag), which
+//! checks the entropy estimates against the distribution of generated
+//! passwords.
+
+mod common;
+
+#[test]
+fn entropy_self_test_passes() {
+    let output = common::run(&common::seeded(&[&["--self-test"]]));
+
+    assert!(
+        output.status.success(),
+        "self-test failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
@@ -0,0 +1,68 @@
+//! Checks that --separator-set chooses each gap's separator from the set, and
+//! that separators given more than once don't add any entropy.
+
+mod common;
+
+/// Run makepass --explain with the given separator set, and return the
+/// entropy it attributes to the separators
+fn separator_entropy(separators: &str) -> f64 {
+    let report = common::makepass(&common::seeded(&[&[
+        "--explain",
+        "--sample-size",
+        "100",
+        "--word-count",
+        "4",
+        "--separator-set",
+        separators,
+    ]]));
+
+    common::json_number(&report, "separators")
+}
+
+#[test]
+fn each_gap_adds_the_entropy_of_the_set() {
+    // 3 gaps, each with one of 2 separators
+    let entropy = separator_entropy("-.");
+    assert!((entropy - 3.0).abs() < 1e-6, "{}", entropy);
+}
+
+#[test]
+fn duplicate_separators_add_no_entropy() {
+    let expected = separator_entropy("-.");
+
+    for separators in ["--.", "-----.", ".-.-", "-.."] {
+        let entropy = separator_entropy(separators);
+        assert!(
+            (entropy - expected).abs() < 1e-6,
+            "{:?}: {}",
+            separators,
+            entropy
+        );
+    }
+
+    assert_eq!(separator_entropy("---"), 0.0);
+}
+
+#[test]
+fn separators_come_from_the_set() {
+    let passwords = common::makepass(&common::seeded(&[&[
+        "--num-passwords",
+        "20",
+        "--word-count",
+        "4",
+        "--no-append-numeral",
+        "--separator-set",
+        "--.",
+    ]]));
+
+    for password in passwords.lines() {
+        let separators: Vec<char> = password.chars().filter(|c| !c.is_alphabetic()).collect();
+
+        assert_eq!(separators.len(), 3, "{:?}", password);
+        assert!(
+            separators.iter().all(|c| "-.".contains(*c)),
+            "{:?}",
+            password
+        );
+    }
+}
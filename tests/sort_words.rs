@@ -0,0 +1,76 @@
+//! Checks that --sort-words sorts the random words in each password, and
+//! that it costs log2(n!) bits of entropy for n random words.
+
+mod common;
+
+/// The words entropy reported by --explain
+fn words_entropy(args: &[&str]) -> f64 {
+    let report = common::makepass(&common::seeded(&[&["--explain"], args]));
+
+    common::json_number(&report, "words")
+}
+
+fn passwords(args: &[&str]) -> Vec<Vec<String>> {
+    let args = common::seeded(&[
+        &["--num-passwords", "20", "--min-length", "0"],
+        &["--separator", " ", "--no-append-numeral"],
+        args,
+    ]);
+
+    common::makepass(&args)
+        .lines()
+        .map(|line| line.split(' ').map(str::to_string).collect())
+        .collect()
+}
+
+#[test]
+fn words_are_sorted() {
+    for words in passwords(&["--word-count", "5", "--sort-words"]) {
+        assert_eq!(words.len(), 5);
+        assert!(
+            words.windows(2).all(|pair| pair[0] <= pair[1]),
+            "{:?}",
+            words
+        );
+    }
+}
+
+#[test]
+fn fixed_words_keep_their_position() {
+    for words in passwords(&["--word-count", "4", "--sort-words", "--fixed-word", "1:zzz"]) {
+        assert_eq!(words[0], "zzz");
+        assert!(
+            words[1..].windows(2).all(|pair| pair[0] <= pair[1]),
+            "{:?}",
+            words
+        );
+    }
+}
+
+#[test]
+fn entropy_drops_by_log_factorial() {
+    // log2(4!) and log2(6!)
+    for (word_count, lost) in [("4", 4.584_962_5), ("6", 9.491_853)] {
+        let unsorted = words_entropy(&["--word-count", word_count]);
+        let sorted = words_entropy(&["--word-count", word_count, "--sort-words"]);
+
+        assert!(
+            (unsorted - sorted - lost).abs() < 1e-3,
+            "{} words: {} - {} != {}",
+            word_count,
+            unsorted,
+            sorted,
+            lost
+        );
+    }
+}
+
+#[test]
+fn fixed_words_dont_count_towards_the_loss() {
+    // Only the 3 random words are sorted, so log2(3!) is lost
+    let args = ["--word-count", "4", "--fixed-word", "1:zzz"];
+    let unsorted = words_entropy(&args);
+    let sorted = words_entropy(&[&args[..], &["--sort-words"]].concat());
+
+    assert!((unsorted - sorted - 6f64.log2()).abs() < 1e-3);
+}
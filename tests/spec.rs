@@ -0,0 +1,105 @@
+//! Checks that --spec parses compact password specs, rejects malformed ones,
+//! and round-trips through the password rules back to the same spec with
+//! --print-spec.
+
+mod common;
+
+use common::makepass;
+
+/// Bind a spec to the default options, and print the spec for the resulting
+/// rules
+fn round_trip(spec: &str) -> String {
+    makepass(&["--spec", spec, "--print-spec"])
+        .trim_end()
+        .to_string()
+}
+
+#[test]
+fn canonical_specs_round_trip() {
+    for spec in ["4w", "4w+n", "4w+s", "4w+n+s", "6w+n+3s", "1w+12s", "10w+n"] {
+        assert_eq!(round_trip(spec), spec);
+    }
+}
+
+#[test]
+fn specs_are_printed_in_canonical_form() {
+    assert_eq!(round_trip("3w+s+n"), "3w+n+s");
+    assert_eq!(round_trip("3w+1s"), "3w+s");
+    assert_eq!(round_trip("03w+n"), "3w+n");
+}
+
+#[test]
+fn options_print_as_a_spec() {
+    let spec = makepass(&[
+        "--print-spec",
+        "--word-count",
+        "5",
+        "--no-append-numeral",
+        "--symbol-count",
+        "2",
+    ]);
+
+    assert_eq!(spec.trim_end(), "5w+2s");
+}
+
+#[test]
+fn zero_words_are_allowed() {
+    assert_eq!(round_trip("0w+n"), "0w+n");
+    assert_eq!(round_trip("0w+s+n"), "0w+n+s");
+    assert_eq!(round_trip("00w+2s"), "0w+2s");
+}
+
+#[test]
+fn zero_words_and_no_extras_is_an_empty_password() {
+    // As with --word-count 0 --no-append-numeral, this parses, but there's
+    // nothing to generate
+    assert_eq!(round_trip("0w"), "0w");
+
+    let stderr = common::failure(common::run(&["--spec", "0w"]));
+    assert!(stderr.contains("the password would be empty"), "{}", stderr);
+}
+
+#[test]
+fn malformed_specs_are_rejected() {
+    for spec in [
+        "", "w", "4", "4w+", "4w+n+n", "4w+s+2s", "4w+0s", "4w+-1s", "4w+x", "4 w", "4w+n+s+",
+        "+4w", "4w,n", "99999w",
+    ] {
+        let output = common::run(&["--spec", spec, "--print-spec"]);
+        assert!(!output.status.success(), "{:?} was accepted", spec);
+    }
+}
+
+#[test]
+fn spec_conflicts_with_the_options_it_sets() {
+    for args in [
+        &["--word-count", "3"][..],
+        &["--no-append-numeral"],
+        &["--symbol-count", "2"],
+    ] {
+        let output = common::run(&[&["--spec", "4w+n"][..], args].concat());
+        assert!(!output.status.success(), "{:?} was accepted", args);
+    }
+}
+
+#[test]
+fn passwords_follow_the_spec() {
+    let passwords = makepass(&common::seeded(&[&[
+        "--spec",
+        "3w+2s",
+        "--num-passwords",
+        "20",
+        "--separator",
+        " ",
+        "--symbol-set",
+        "#",
+        "--min-length",
+        "0",
+    ]]));
+
+    for password in passwords.lines() {
+        assert_eq!(password.split(' ').count(), 3, "{:?}", password);
+        assert!(password.ends_with("##"), "{:?}", password);
+        assert!(!password.contains(char::is_numeric), "{:?}", password);
+    }
+}
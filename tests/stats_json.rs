@@ -0,0 +1,80 @@
+//! Checks the summary written by --stats-json against the passwords that were
+//! actually printed.
+
+mod common;
+
+use common::json_number as number;
+
+const WORDLIST: &[&str] = &["apple", "bacon", "cedar", "delta", "eagle", "fable"];
+
+/// Run makepass with --stats-json going to stderr, and return the passwords
+/// and the statistics
+fn run(args: &[&str]) -> (Vec<String>, String) {
+    let args = common::seeded(&[&["--min-length", "0", "--stats-json", "-"], args]);
+
+    let (stdout, stderr) = common::success(common::run_with_stdin(
+        &[&["--wordlist", "stdin"][..], &args].concat(),
+        WORDLIST.join("\n"),
+    ));
+
+    let passwords = stdout.lines().map(str::to_string).collect();
+
+    let stats = stderr
+        .lines()
+        .find(|line| line.starts_with('{'))
+        .expect("no statistics on stderr")
+        .to_string();
+
+    (passwords, stats)
+}
+
+#[test]
+fn stats_match_the_passwords() {
+    let (passwords, stats) = run(&[
+        "--num-passwords",
+        "30",
+        "--word-count",
+        "2",
+        "--separator",
+        "-",
+        "--no-append-numeral",
+    ]);
+
+    assert_eq!(passwords.len(), 30);
+    assert_eq!(number(&stats, "passwords"), 30.0);
+    assert_eq!(number(&stats, "wordlist_size"), WORDLIST.len() as f64);
+
+    let lengths: Vec<usize> = passwords.iter().map(String::len).collect();
+    let length = common::json_value(&stats, "length").expect("no length");
+
+    assert_eq!(number(length, "min"), *lengths.iter().min().unwrap() as f64);
+    assert_eq!(number(length, "max"), *lengths.iter().max().unwrap() as f64);
+
+    let mean = lengths.iter().sum::<usize>() as f64 / lengths.len() as f64;
+    assert!((number(length, "mean") - mean).abs() < 1e-9, "{}", stats);
+
+    let mut words: Vec<String> = passwords
+        .iter()
+        .flat_map(|password| password.split('-'))
+        .map(str::to_lowercase)
+        .collect();
+    words.sort();
+    words.dedup();
+
+    assert_eq!(number(&stats, "distinct_words"), words.len() as f64);
+
+    let rate = number(&stats, "acceptance_rate");
+    assert!(rate > 0.0 && rate <= 1.0, "{}", stats);
+}
+
+#[test]
+fn acceptance_rate_is_null_without_a_sample() {
+    let (_, stats) = run(&["--num-passwords", "3", "--sample-size", "0"]);
+
+    assert_eq!(
+        common::json_value(&stats, "acceptance_rate"),
+        Some("null"),
+        "{}",
+        stats
+    );
+}
@@ -0,0 +1,43 @@
+//! Checks that --words-from-stdin-only rejects the builtin wordlists, but
+//! still accepts wordlists from stdin or a file.
+
+mod common;
+
+#[test]
+fn named_wordlists_are_rejected() {
+    for args in [
+        &["--wordlist", "english"][..],
+        &["--wordlist", "default"],
+        &[],
+    ] {
+        let stderr = common::failure(common::run(
+            &[&["--words-from-stdin-only"][..], args].concat(),
+        ));
+
+        assert!(
+            stderr.contains("can't be used with --words-from-stdin-only"),
+            "{:?}: {}",
+            args,
+            stderr
+        );
+    }
+}
+
+#[test]
+fn stdin_wordlists_are_accepted() {
+    common::makepass_with_words(
+        &["apple", "bacon", "cedar", "delta"],
+        &["--words-from-stdin-only", "--min-length", "0"],
+    );
+}
+
+#[test]
+fn wordlist_files_are_accepted() {
+    common::makepass(&[
+        "--words-from-stdin-only",
+        "--wordlist-file",
+        concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/compound.list"),
+        "--min-length",
+        "0",
+    ]);
+}
@@ -0,0 +1,45 @@
+//! Checks the separator and capitalization of each --style preset.
+
+mod common;
+
+/// Generate a password of three words, in the given style, from a wordlist
+/// of three words, sorted so that the output is predictable
+fn styled(style: &str) -> String {
+    common::makepass_with_words(
+        &["hOrSe", "bAtTeRy", "cOrReCt"],
+        &[
+            &["--min-word", "1", "--min-length", "0"][..],
+            &["--word-count", "3", "--no-append-numeral", "--sort-words"],
+            &["--newline", "never", "--style", style],
+        ]
+        .concat(),
+    )
+}
+
+#[test]
+fn each_style_sets_the_separator_and_case() {
+    for (style, expected) in [
+        ("lower", "batterycorrecthorse"),
+        ("kebab", "battery-correct-horse"),
+        ("snake", "battery_correct_horse"),
+        ("camel", "batteryCorrectHorse"),
+        ("pascal", "BatteryCorrectHorse"),
+        ("title", "Battery Correct Horse"),
+    ] {
+        assert_eq!(styled(style), expected, "{}", style);
+    }
+}
+
+#[test]
+fn style_conflicts_with_separator_and_case() {
+    for args in [
+        &["--separator", "."][..],
+        &["--separator-set", ".-"],
+        &["--all-upper"],
+        &["--random-case"],
+    ] {
+        let output = common::run(&[&["--style", "kebab"][..], args].concat());
+
+        assert!(!output.status.success(), "{:?} was accepted", args);
+    }
+}
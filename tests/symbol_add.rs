@@ -0,0 +1,53 @@
+//! Checks that --symbol-add extends the symbol set, and that symbols given more
+//! than once don't add any entropy.
+
+mod common;
+
+/// Run makepass --explain with the given extra arguments, and return the
+/// entropy it attributes to the symbols
+fn symbol_entropy(args: &[&str]) -> f64 {
+    let report = common::makepass(&common::seeded(&[
+        &["--explain", "--sample-size", "100"],
+        args,
+    ]));
+
+    common::json_number(&report, "symbol")
+}
+
+#[test]
+fn symbol_add_extends_the_symbol_set() {
+    let entropy = symbol_entropy(&["--symbol-set", "ab", "--symbol-add", "cd"]);
+
+    assert!((entropy - 2.0).abs() < 1e-6, "{}", entropy);
+}
+
+#[test]
+fn duplicate_symbols_add_no_entropy() {
+    let expected = symbol_entropy(&["--symbol-set", "abc"]);
+
+    for args in [
+        &[
+            "--symbol-set",
+            "ab",
+            "--symbol-add",
+            "bc",
+            "--symbol-add",
+            "ca",
+        ][..],
+        &["--symbol-set", "aabbc"],
+        &["--symbol-set", "abc", "--symbol-add", "cba"],
+    ] {
+        let entropy = symbol_entropy(args);
+        assert!((entropy - expected).abs() < 1e-6, "{:?}: {}", args, entropy);
+    }
+}
+
+#[test]
+fn symbol_add_extends_the_default_set() {
+    let default = symbol_entropy(&["--append-symbol"]);
+    let extended = symbol_entropy(&["--symbol-add", "€£"]);
+    let duplicated = symbol_entropy(&["--symbol-add", "!?"]);
+
+    assert!(extended > default, "{} <= {}", extended, default);
+    assert!((duplicated - default).abs() < 1e-6, "{}", duplicated);
+}
@@ -0,0 +1,92 @@
+//! Checks that --unique drops passwords that are the same passphrase as an
+//! earlier one in the batch, even with their words in a different order.
+
+use std::process::Output;
+
+mod common;
+
+const WORDLIST: &[&str] = &["apple", "bacon", "cedar", "delta"];
+
+/// Generate two-word passwords from WORDLIST, separated by "-"
+fn makepass(args: &[&str]) -> Output {
+    common::run_with_stdin(
+        &common::seeded(&[
+            &[
+                "--wordlist",
+                "stdin",
+                "--word-count",
+                "2",
+                "--separator",
+                "-",
+            ],
+            &[
+                "--min-length",
+                "0",
+                "--no-append-numeral",
+                "--retries",
+                "1000",
+            ],
+            args,
+        ]),
+        WORDLIST.join("\n"),
+    )
+}
+
+/// The words of each password, sorted, so that reorderings compare equal
+fn passphrases(output: &Output) -> Vec<Vec<String>> {
+    assert!(output.status.success(), "makepass failed: {:?}", output);
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|password| {
+            let mut words: Vec<String> = password.split('-').map(str::to_lowercase).collect();
+            words.sort();
+            words
+        })
+        .collect()
+}
+
+fn has_duplicates(passphrases: &[Vec<String>]) -> bool {
+    passphrases
+        .iter()
+        .enumerate()
+        .any(|(i, passphrase)| passphrases[..i].contains(passphrase))
+}
+
+#[test]
+fn reordered_passphrases_are_dropped() {
+    // There are 6 pairs of distinct words, each in 2 orders
+    let passphrases = passphrases(&makepass(&["--num-passwords", "6", "--unique"]));
+
+    assert_eq!(passphrases.len(), 6);
+    assert!(!has_duplicates(&passphrases), "{:?}", passphrases);
+}
+
+#[test]
+fn sorted_passphrases_are_dropped() {
+    let passphrases = passphrases(&makepass(&[
+        "--num-passwords",
+        "6",
+        "--unique",
+        "--sort-words",
+    ]));
+
+    assert_eq!(passphrases.len(), 6);
+    assert!(!has_duplicates(&passphrases), "{:?}", passphrases);
+}
+
+#[test]
+fn duplicates_are_kept_by_default() {
+    let passphrases = passphrases(&makepass(&["--num-passwords", "30"]));
+
+    assert_eq!(passphrases.len(), 30);
+    assert!(has_duplicates(&passphrases));
+}
+
+#[test]
+fn running_out_of_passphrases_fails() {
+    let output = makepass(&["--num-passwords", "7", "--unique"]);
+
+    assert!(!output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 6);
+}
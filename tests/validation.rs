@@ -0,0 +1,73 @@
+//! Checks that inconsistent options are rejected with a clear error, before
+//! any passwords are generated.
+
+mod common;
+
+/// Run makepass, and return its stderr if it failed, or None if it succeeded
+fn error(args: &[&str]) -> Option<String> {
+    let output = common::run(&common::seeded(&[args]));
+
+    match output.status.success() {
+        true => None,
+        false => {
+            assert!(output.stdout.is_empty(), "{:?}", output);
+            Some(common::failure(output))
+        }
+    }
+}
+
+fn assert_error(args: &[&str], message: &str) {
+    let stderr = error(args).unwrap_or_else(|| panic!("{:?} succeeded", args));
+    assert!(stderr.contains(message), "{:?}: {}", args, stderr);
+}
+
+#[test]
+fn word_length_bounds_must_be_ordered() {
+    assert_error(
+        &["--min-word", "7", "--max-word", "5"],
+        "Invalid word length: minimum length 7 greater than maximum length 5",
+    );
+}
+
+#[test]
+fn password_length_bounds_must_be_ordered() {
+    assert_error(
+        &["--min-length", "30", "--max-length", "10"],
+        "Invalid password length: minimum length 30 greater than maximum length 10",
+    );
+}
+
+#[test]
+fn word_length_is_checked_with_exact_length() {
+    assert_error(
+        &["--exact-length", "30", "--min-word", "9", "--max-word", "2"],
+        "Invalid word length",
+    );
+}
+
+#[test]
+fn each_class_requires_a_numeral() {
+    assert_error(
+        &["--require-each-class", "--no-append-numeral"],
+        "--require-each-class requires a numeral",
+    );
+}
+
+#[test]
+fn each_class_requires_a_symbol() {
+    assert_error(
+        &["--require-each-class", "--no-append-symbol"],
+        "--require-each-class requires a symbol",
+    );
+    assert_error(
+        &["--require-each-class", "--symbol-count", "0"],
+        "--require-each-class requires a symbol",
+    );
+}
+
+#[test]
+fn consistent_options_are_accepted() {
+    assert_eq!(error(&["--require-each-class"]), None);
+    assert_eq!(error(&["--min-word", "6", "--max-word", "6"]), None);
+    assert_eq!(error(&["--min-length", "20", "--max-length", "40"]), None);
+}
@@ -0,0 +1,51 @@
+//! Checks how --min-word and --max-word combine with the default word length
+//! bounds (4 to 8), for each combination of given and missing options.
+
+mod common;
+
+/// Filter a wordlist with one word of each length from 1 to 12 with the given
+/// arguments, and return the lengths of the words that are left. Returns None
+/// if makepass fails.
+fn word_lengths(args: &[&str]) -> Option<Vec<usize>> {
+    let wordlist: Vec<String> = (1..=12).map(|length| "a".repeat(length)).collect();
+
+    let output = common::run_with_stdin(
+        &[
+            &["--wordlist", "stdin", "--print-filtered-wordlist"][..],
+            args,
+        ]
+        .concat(),
+        wordlist.join("\n"),
+    );
+
+    output
+        .status
+        .success()
+        .then(|| common::success(output).0.lines().map(str::len).collect())
+}
+
+#[test]
+fn neither_bound_uses_the_defaults() {
+    assert_eq!(word_lengths(&[]), Some(vec![4, 5, 6, 7, 8]));
+}
+
+#[test]
+fn min_only_raises_the_default_max() {
+    assert_eq!(word_lengths(&["--min-word", "6"]), Some(vec![6, 7, 8]));
+    assert_eq!(word_lengths(&["--min-word", "10"]), Some(vec![10]));
+}
+
+#[test]
+fn max_only_lowers_the_default_min() {
+    assert_eq!(word_lengths(&["--max-word", "6"]), Some(vec![4, 5, 6]));
+    assert_eq!(word_lengths(&["--max-word", "2"]), Some(vec![2]));
+}
+
+#[test]
+fn both_bounds_are_used_as_given() {
+    assert_eq!(
+        word_lengths(&["--min-word", "2", "--max-word", "10"]),
+        Some(vec![2, 3, 4, 5, 6, 7, 8, 9, 10])
+    );
+    assert_eq!(word_lengths(&["--min-word", "7", "--max-word", "5"]), None);
+}
@@ -0,0 +1,54 @@
+//! Checks that --wordlist-hash prints the SHA-256 of the sorted, filtered
+//! wordlist, to stderr, or to stdout with --format.
+
+mod common;
+
+/// The SHA-256 of "apple\nbacon\ncedar\n"
+const DIGEST: &str = "448385899807e73fb536da39a65415095feaa3186d54f62e1da8df0f9c7e9bf9";
+
+fn wordlist_hash(words: &str, args: &[&str]) -> (String, String) {
+    let args = common::seeded(&[
+        &[
+            "--wordlist",
+            "stdin",
+            "--min-length",
+            "0",
+            "--word-count",
+            "2",
+        ],
+        &["--wordlist-hash"],
+        args,
+    ]);
+
+    common::success(common::run_with_stdin(&args, words))
+}
+
+#[test]
+fn digest_is_of_the_sorted_words() {
+    for words in ["apple\nbacon\ncedar", "cedar\napple\nbacon\n"] {
+        let (_, stderr) = wordlist_hash(words, &[]);
+
+        assert!(
+            stderr.contains(&format!("Wordlist SHA-256: {}", DIGEST)),
+            "{}",
+            stderr
+        );
+    }
+}
+
+#[test]
+fn digest_is_of_the_filtered_words() {
+    let (_, stderr) = wordlist_hash("apple\nbacon\ncedar\nx\nextraordinarily", &[]);
+
+    assert!(stderr.contains(DIGEST), "{}", stderr);
+}
+
+#[test]
+fn format_prints_the_digest_to_stdout() {
+    let (stdout, stderr) = wordlist_hash("apple\nbacon\ncedar", &["--format", "csv"]);
+    let mut lines = stdout.lines();
+
+    assert_eq!(lines.next(), Some(DIGEST));
+    assert!(lines.next().unwrap().starts_with("password,"), "{}", stdout);
+    assert!(!stderr.contains(DIGEST), "{}", stderr);
+}
@@ -0,0 +1,47 @@
+//! Checks --word-count 0, which makes a short token of just the numeral and
+//! symbols.
+
+use std::process::Output;
+
+mod common;
+
+fn makepass(args: &[&str]) -> Output {
+    common::run(&common::seeded(&[&["--word-count", "0"], args]))
+}
+
+#[test]
+fn zero_words_is_just_the_decorations() {
+    let (stdout, _) = common::success(makepass(&[
+        "--num-passwords",
+        "20",
+        "--symbol-set",
+        "!?",
+        "--symbol-count",
+        "2",
+    ]));
+    assert_eq!(stdout.lines().count(), 20);
+
+    for password in stdout.lines() {
+        let (numeral, symbols) = password.split_at(1);
+
+        assert!(
+            numeral.chars().all(|c| c.is_ascii_digit()),
+            "{:?}",
+            password
+        );
+        assert_eq!(symbols.len(), 2, "{:?}", password);
+        assert!(symbols.chars().all(|c| "!?".contains(c)), "{:?}", password);
+    }
+}
+
+#[test]
+fn explicit_min_length_still_applies() {
+    common::failure(makepass(&["--append-symbol", "--min-length", "3"]));
+}
+
+#[test]
+fn empty_password_is_an_error() {
+    let stderr = common::failure(makepass(&["--no-append-numeral"]));
+
+    assert!(stderr.contains("empty"), "{}", stderr);
+}
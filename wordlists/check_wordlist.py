@@ -6,15 +6,20 @@
 # - Any leading or trailing whitespace is removed
 # - Empty lines are ignored and kept
 # - Lines starting with # are ignored and kept
-# - The word is comprised only of alphabetic characters. If this fails, the
-#   script throws an error
-# - The word is title-cased
+# - The word is comprised only of alphabetic characters, plus (optionally) the
+#   punctuation characters in $WORDLIST_PUNCTUATION (defaulting to - and '). If
+#   this fails, the script throws an error
+# - The word is title-cased (only the first letter is capitalized, so that
+#   "o'clock" becomes "O'clock" rather than "O'Clock")
 #
 # Because this script operates on stdin and stdout, it is recommended that you
 # use it with a utility like rewrite (https://github.com/Lucretiel/rewrite) or
 # sponge (https://joeyh.name/code/moreutils/) to a wordlist file in-place
+import os
 import sys
 
+punctuation = os.environ.get("WORDLIST_PUNCTUATION", "-'")
+
 words = set()
 
 for line_number, line in enumerate(sys.stdin, 1):
@@ -32,11 +37,11 @@ for line_number, line in enumerate(sys.stdin, 1):
 
 	# Note: python uses a different definition of "alphabetic" than Rust does. This probably
 	# doesn't matter in practice.
-	if not line.isalpha():
+	if not any(c.isalpha() for c in line) or not all(c.isalpha() or c in punctuation for c in line):
 		print("Non-alphabetic word found on line {}: {}".format(line_number, line), file=sys.stderr)
 		sys.exit(1)
 
-	line = line.title()
+	line = line[:1].upper() + line[1:].lower()
 	if line in words:
 		print("Duplicate word found on line {}: {}".format(line_number, line), file=sys.stderr)
 		sys.exit(1)